@@ -4,15 +4,24 @@ use tauri::{AppHandle, State};
 
 use crate::changelist;
 use crate::git;
+use crate::lint;
 use crate::model::{
-    AppVersion, BranchCreateResult, BranchList, CheckoutResult, RepoBranchListRequest,
-    RepoCheckoutRequest, RepoCreateBranchRequest, RepoDiffRequest, RepoFetchRequest, RepoOpenRequest,
-    Changelist, ChangelistAssignHunksRequest, ChangelistAssignRequest, ChangelistCreateRequest,
-    ChangelistIdRequest, ChangelistRenameRequest, ChangelistState, ChangelistUnassignHunksRequest,
-    ChangelistUnassignRequest, CommitExecuteRequest, CommitPreview, CommitPrepareRequest,
-    CommitResult, DiffHunk, HunkAssignment, RepoDiffPayload, RepoOpenWorktreeRequest, RepoPathRequest,
-    RepoStatusRequest, RepoSummary, UnifiedDiffText, WorktreeAddRequest, WorktreeList,
-    WorktreePathRequest, WorktreeResult,
+    AppVersion, BlameResult, BranchCreateResult, BranchList, CheckoutMode, CheckoutResult,
+    RepoBlameRequest, RepoBranchDeleteRequest, RepoBranchListRequest, RepoBranchRenameRequest,
+    RepoBranchUpstreamRequest, RepoCheckoutRequest, RepoCherryPickRequest, RepoCommitDetailRequest,
+    RepoCreateBranchRequest, RepoDiffRequest, RepoFileHistoryRequest, RepoFetchRequest,
+    RepoOpenRequest, Changelist, ChangelistAssignHunksRequest, ChangelistAssignRequest,
+    ChangelistCreateRequest, ChangelistDraftRequest, ChangelistIdRequest, ChangelistRenameRequest,
+    ChangelistState, ChangelistUnassignHunksRequest, ChangelistUnassignRequest, CommitApplyResult,
+    CommitDetail, CommitExecuteRequest, CommitLintConfig, CommitPreview, CommitPrepareRequest,
+    CommitResult, CommitWarning, ConflictResolveRequest, ConflictVersions, DiffHunk,
+    FileHistoryPage, HunkAssignment, LogPage, PullResult, PushResult, RangeDiff, RepoDiffPayload,
+    RepoOpenWorktreeRequest, RepoPathRequest, RepoLogRequest, RepoPullRequest, RepoPushRequest,
+    RepoRangeDiffRequest, RepoRemoteBranchDeleteRequest, RepoResetRequest, RepoRevertRequest,
+    RepoStatusRequest, RepoSummary, RepoTagCreateRequest, RepoTagDeleteRequest, RepoTagPushRequest,
+    ResetResult, Severity, ShelfEntry, ShelfIdRequest, StashDetail, StashEntry, StashEntryRequest,
+    StashIndexRequest, StashSaveRequest, TagInfo, UnifiedDiffText, UnshelveResult,
+    WorktreeAddRequest, WorktreeList, WorktreePathRequest, WorktreeResult,
 };
 use crate::store::{now_ms, AppState};
 use std::time::Instant;
@@ -137,7 +146,18 @@ pub async fn repo_diff(
         guard.get_repo(&req.repo_id)
     };
     let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
-    let cache_key = git::diff_cache_key(&summary, &req.path, req.kind.clone()).ok();
+    let (oids, cache_key) = {
+        let summary = summary.clone();
+        let path = req.path.clone();
+        let kind = req.kind.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let oids = git::tree_diff_oids(&summary, &kind)?;
+            let cache_key = git::diff_cache_key(&summary, &path, kind, oids.as_ref()).ok();
+            Ok::<_, String>((oids, cache_key))
+        })
+        .await
+        .map_err(|_| "diff job failed".to_string())??
+    };
     if let Some(key) = cache_key.as_ref() {
         if let Ok(guard) = state.lock() {
             if let Some(cached) = guard.get_diff_cache(key) {
@@ -155,11 +175,21 @@ pub async fn repo_diff(
     let path = req.path.clone();
     let kind = req.kind.clone();
     let start = Instant::now();
-    let diff = tauri::async_runtime::spawn_blocking(move || {
-        git::diff_for_path(&summary_for_job, &path, kind)
-    })
-    .await
-    .map_err(|_| "diff job failed".to_string())??;
+    let diff = match oids {
+        Some((old_oid, new_oid)) => {
+            let files = tree_diff_files(&summary, old_oid, new_oid, &state).await?;
+            let text = files
+                .get(&git::normalize_repo_path(&req.path))
+                .cloned()
+                .unwrap_or_default();
+            UnifiedDiffText { text }
+        }
+        None => tauri::async_runtime::spawn_blocking(move || {
+            git::diff_for_path(&summary_for_job, &path, kind)
+        })
+        .await
+        .map_err(|_| "diff job failed".to_string())??,
+    };
 
     tracing::info!(
         repo_id = %summary.repo_id,
@@ -182,6 +212,38 @@ pub async fn repo_diff(
     Ok(diff)
 }
 
+/// Returns every file patch of a commit or range diff, computing the tree
+/// diff once per oid pair.
+async fn tree_diff_files(
+    summary: &RepoSummary,
+    old_oid: Option<String>,
+    new_oid: String,
+    state: &State<'_, Mutex<AppState>>,
+) -> Result<std::sync::Arc<std::collections::HashMap<String, String>>, String> {
+    let key = format!(
+        "{}:{}:{}",
+        summary.repo_id,
+        old_oid.as_deref().unwrap_or("none"),
+        new_oid
+    );
+    if let Ok(guard) = state.lock() {
+        if let Some(cached) = guard.get_tree_diff_cache(&key) {
+            return Ok(cached);
+        }
+    }
+
+    let summary = summary.clone();
+    let files = tauri::async_runtime::spawn_blocking(move || {
+        git::tree_diff_texts(&summary, old_oid.as_deref(), &new_oid)
+    })
+    .await
+    .map_err(|_| "diff job failed".to_string())??;
+    let files = std::sync::Arc::new(files);
+    let mut guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+    guard.set_tree_diff_cache(key, files.clone());
+    Ok(files)
+}
+
 #[tauri::command]
 pub async fn repo_diff_hunks(
     req: RepoDiffRequest,
@@ -194,7 +256,16 @@ pub async fn repo_diff_hunks(
     let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
     let path = req.path.clone();
     let kind = req.kind.clone();
-    let cache_key = git::diff_cache_key(&summary, &path, kind.clone()).ok();
+    let cache_key = {
+        let path = path.clone();
+        let kind = kind.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let oids = git::tree_diff_oids(&summary, &kind).ok()?;
+            git::diff_cache_key(&summary, &path, kind, oids.as_ref()).ok()
+        })
+        .await
+        .map_err(|_| "diff job failed".to_string())?
+    };
     if let Some(key) = cache_key.as_ref() {
         if let Ok(guard) = state.lock() {
             if let Some(cached) = guard.get_diff_cache(key) {
@@ -244,120 +315,927 @@ pub async fn repo_diff_payload(
     })
 }
 
-#[tauri::command]
-pub async fn wt_list(req: WorktreePathRequest) -> Result<WorktreeList, String> {
-    tauri::async_runtime::spawn_blocking(move || git::list_worktrees(&req.repo_root))
+#[tauri::command]
+pub async fn wt_list(req: WorktreePathRequest) -> Result<WorktreeList, String> {
+    tauri::async_runtime::spawn_blocking(move || git::list_worktrees(&req.repo_root))
+        .await
+        .map_err(|_| "worktree list failed".to_string())?
+}
+
+#[tauri::command]
+pub async fn wt_add(req: WorktreeAddRequest) -> Result<WorktreeResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        git::add_worktree(&req.repo_root, &req.path, &req.branch_name, req.new_branch)
+    })
+    .await
+    .map_err(|_| "worktree add failed".to_string())?
+}
+
+#[tauri::command]
+pub async fn wt_remove(req: WorktreePathRequest) -> Result<WorktreeResult, String> {
+    tauri::async_runtime::spawn_blocking(move || git::remove_worktree(&req.repo_root, &req.path))
+        .await
+        .map_err(|_| "worktree remove failed".to_string())?
+}
+
+#[tauri::command]
+pub async fn wt_prune(req: WorktreePathRequest) -> Result<WorktreeResult, String> {
+    tauri::async_runtime::spawn_blocking(move || git::prune_worktrees(&req.repo_root))
+        .await
+        .map_err(|_| "worktree prune failed".to_string())?
+}
+
+#[tauri::command]
+pub async fn repo_stage(
+    req: RepoPathRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    git::stage_path(&summary, &req.path)?;
+    if let Err(error) = refresh_cached_status(&summary, &state) {
+        tracing::warn!(
+            repo_id = %summary.repo_id,
+            path = %req.path,
+            error = %error,
+            "failed to refresh cached status after stage"
+        );
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn repo_track(
+    req: RepoPathRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    git::track_path(&summary, &req.path)?;
+    if let Err(error) = refresh_cached_status(&summary, &state) {
+        tracing::warn!(
+            repo_id = %summary.repo_id,
+            path = %req.path,
+            error = %error,
+            "failed to refresh cached status after track"
+        );
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn repo_unstage(
+    req: RepoPathRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    git::unstage_path(&summary, &req.path)?;
+    if let Err(error) = refresh_cached_status(&summary, &state) {
+        tracing::warn!(
+            repo_id = %summary.repo_id,
+            path = %req.path,
+            error = %error,
+            "failed to refresh cached status after unstage"
+        );
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn repo_conflict_versions(
+    req: RepoPathRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ConflictVersions, String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    git::conflict_versions(&summary, &req.path)
+}
+
+#[tauri::command]
+pub async fn repo_conflict_resolve(
+    req: ConflictResolveRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    git::resolve_conflict(&summary, &req.path, &req.resolution)?;
+    if let Err(error) = refresh_cached_status(&summary, &state) {
+        tracing::warn!(
+            repo_id = %summary.repo_id,
+            path = %req.path,
+            error = %error,
+            "failed to refresh cached status after conflict resolve"
+        );
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn repo_conflict_mark_resolved(
+    req: RepoPathRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    git::mark_conflict_resolved(&summary, &req.path)?;
+    if let Err(error) = refresh_cached_status(&summary, &state) {
+        tracing::warn!(
+            repo_id = %summary.repo_id,
+            path = %req.path,
+            error = %error,
+            "failed to refresh cached status after marking resolved"
+        );
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn repo_branches(
+    req: RepoBranchListRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<BranchList, String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    git::list_branches(&summary)
+}
+
+#[tauri::command]
+pub async fn repo_checkout(
+    req: RepoCheckoutRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<CheckoutResult, crate::model::RepoError> {
+    let summary = {
+        let guard = state
+            .lock()
+            .map_err(|_| crate::model::RepoError::GitError {
+                message: "state lock failed".to_string(),
+            })?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| crate::model::RepoError::GitError {
+        message: "unknown repo id".to_string(),
+    })?;
+
+    let summary_for_job = summary.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        // Stashing moves every local change out of the working tree, so the
+        // assignments are captured first and re-applied to whatever comes back.
+        let previous = match req.mode {
+            CheckoutMode::Stash => changelist::load_state(&summary_for_job).ok(),
+            _ => None,
+        };
+        let result = git::checkout_branch(&summary_for_job, &req.target, req.mode)?;
+        if let Some(previous) = previous {
+            if let Err(error) = changelist::restore_after_checkout(&summary_for_job, &previous) {
+                tracing::warn!(
+                    repo_id = %summary_for_job.repo_id,
+                    error = %error,
+                    "failed to restore changelist assignments after checkout"
+                );
+            }
+        }
+        Ok(result)
+    })
+        .await
+        .map_err(|_| crate::model::RepoError::GitError {
+            message: "checkout job failed".to_string(),
+        })??;
+
+    // Update cached status after checkout so UI refreshes quickly.
+    if let Ok(mut status) = git::status(&summary) {
+        if let Ok(mut cl_state) = changelist::load_state(&summary) {
+            let _ = changelist::apply_to_status(&summary, &mut cl_state, &mut status);
+        }
+        if let Ok(mut guard) = state.lock() {
+            guard.set_status(status);
+        }
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn repo_create_branch(
+    req: RepoCreateBranchRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<BranchCreateResult, String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    let head = git::create_branch(&summary, &req.name, req.from.as_deref(), req.checkout)?;
+    if head.is_some() {
+        if let Err(error) = refresh_cached_status(&summary, &state) {
+            tracing::warn!(
+                repo_id = %summary.repo_id,
+                error = %error,
+                "failed to refresh cached status after branch checkout"
+            );
+        }
+    }
+    Ok(BranchCreateResult {
+        name: req.name,
+        head,
+    })
+}
+
+#[tauri::command]
+pub async fn repo_branch_delete(
+    req: RepoBranchDeleteRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), crate::model::RepoError> {
+    let summary = {
+        let guard = state
+            .lock()
+            .map_err(|_| crate::model::RepoError::GitError {
+                message: "state lock failed".to_string(),
+            })?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| crate::model::RepoError::GitError {
+        message: "unknown repo id".to_string(),
+    })?;
+
+    tauri::async_runtime::spawn_blocking(move || git::delete_branch(&summary, &req.name, req.force))
+        .await
+        .map_err(|_| crate::model::RepoError::GitError {
+            message: "branch delete job failed".to_string(),
+        })?
+}
+
+#[tauri::command]
+pub async fn repo_branch_rename(
+    req: RepoBranchRenameRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), crate::model::RepoError> {
+    let summary = {
+        let guard = state
+            .lock()
+            .map_err(|_| crate::model::RepoError::GitError {
+                message: "state lock failed".to_string(),
+            })?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| crate::model::RepoError::GitError {
+        message: "unknown repo id".to_string(),
+    })?;
+
+    let summary_for_job = summary.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        git::rename_branch(&summary_for_job, &req.name, &req.new_name)
+    })
+    .await
+    .map_err(|_| crate::model::RepoError::GitError {
+        message: "branch rename job failed".to_string(),
+    })??;
+
+    // Renaming the current branch changes the reported head.
+    if let Err(error) = refresh_cached_status(&summary, &state) {
+        tracing::warn!(
+            repo_id = %summary.repo_id,
+            error = %error,
+            "failed to refresh cached status after branch rename"
+        );
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn repo_branch_set_upstream(
+    req: RepoBranchUpstreamRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), crate::model::RepoError> {
+    let summary = {
+        let guard = state
+            .lock()
+            .map_err(|_| crate::model::RepoError::GitError {
+                message: "state lock failed".to_string(),
+            })?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| crate::model::RepoError::GitError {
+        message: "unknown repo id".to_string(),
+    })?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        git::set_upstream(&summary, &req.name, req.upstream.as_deref())
+    })
+    .await
+    .map_err(|_| crate::model::RepoError::GitError {
+        message: "set upstream job failed".to_string(),
+    })?
+}
+
+#[tauri::command]
+pub async fn repo_remote_branch_delete(
+    req: RepoRemoteBranchDeleteRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), crate::model::RepoError> {
+    let summary = {
+        let guard = state
+            .lock()
+            .map_err(|_| crate::model::RepoError::GitError {
+                message: "state lock failed".to_string(),
+            })?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| crate::model::RepoError::GitError {
+        message: "unknown repo id".to_string(),
+    })?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        git::delete_remote_branch(
+            &summary,
+            req.remote.as_deref(),
+            &req.branch,
+            req.credentials.as_ref(),
+        )
+    })
+    .await
+    .map_err(|_| crate::model::RepoError::GitError {
+        message: "remote branch delete job failed".to_string(),
+    })?
+}
+
+#[tauri::command]
+pub async fn repo_tags(
+    req: RepoBranchListRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<TagInfo>, String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    git::list_tags(&summary)
+}
+
+#[tauri::command]
+pub async fn repo_tag_create(
+    req: RepoTagCreateRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<TagInfo, String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    git::create_tag(
+        &summary,
+        &req.name,
+        req.revision.as_deref(),
+        req.message.as_deref(),
+    )
+}
+
+#[tauri::command]
+pub async fn repo_tag_delete(
+    req: RepoTagDeleteRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    git::delete_tag(&summary, &req.name)
+}
+
+#[tauri::command]
+pub async fn repo_tag_push(
+    req: RepoTagPushRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), crate::model::RepoError> {
+    let summary = {
+        let guard = state
+            .lock()
+            .map_err(|_| crate::model::RepoError::GitError {
+                message: "state lock failed".to_string(),
+            })?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| crate::model::RepoError::GitError {
+        message: "unknown repo id".to_string(),
+    })?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        git::push_tags(
+            &summary,
+            req.remote.as_deref(),
+            req.name.as_deref(),
+            req.credentials.as_ref(),
+        )
+    })
+    .await
+    .map_err(|_| crate::model::RepoError::GitError {
+        message: "tag push job failed".to_string(),
+    })?
+}
+
+#[tauri::command]
+pub async fn repo_fetch(
+    req: RepoFetchRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<crate::model::FetchResult, crate::model::RepoError> {
+    let summary = {
+        let guard = state
+            .lock()
+            .map_err(|_| crate::model::RepoError::GitError {
+                message: "state lock failed".to_string(),
+            })?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| crate::model::RepoError::GitError {
+        message: "unknown repo id".to_string(),
+    })?;
+    let remote = req.remote.clone().unwrap_or_else(|| "origin".to_string());
+    let updated = tauri::async_runtime::spawn_blocking(move || {
+        git::fetch(&summary, req.remote.as_deref(), req.credentials.as_ref())
+    })
+    .await
+    .map_err(|_| crate::model::RepoError::GitError {
+        message: "fetch job failed".to_string(),
+    })??;
+    Ok(crate::model::FetchResult { remote, updated })
+}
+
+#[tauri::command]
+pub async fn repo_pull(
+    req: RepoPullRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<PullResult, crate::model::RepoError> {
+    let summary = {
+        let guard = state
+            .lock()
+            .map_err(|_| crate::model::RepoError::GitError {
+                message: "state lock failed".to_string(),
+            })?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| crate::model::RepoError::GitError {
+        message: "unknown repo id".to_string(),
+    })?;
+
+    let summary_for_job = summary.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        git::pull(&summary_for_job, req.strategy, req.credentials.as_ref())
+    })
+    .await
+    .map_err(|_| crate::model::RepoError::GitError {
+        message: "pull job failed".to_string(),
+    })?;
+
+    // Refresh even on conflicts so the UI shows the conflicted files.
+    if let Err(error) = refresh_cached_status(&summary, &state) {
+        tracing::warn!(
+            repo_id = %summary.repo_id,
+            error = %error,
+            "failed to refresh cached status after pull"
+        );
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn repo_push(
+    req: RepoPushRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<PushResult, crate::model::RepoError> {
+    let summary = {
+        let guard = state
+            .lock()
+            .map_err(|_| crate::model::RepoError::GitError {
+                message: "state lock failed".to_string(),
+            })?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| crate::model::RepoError::GitError {
+        message: "unknown repo id".to_string(),
+    })?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        git::push(
+            &summary,
+            req.remote.as_deref(),
+            req.branch.as_deref(),
+            req.set_upstream,
+            req.credentials.as_ref(),
+        )
+    })
+    .await
+    .map_err(|_| crate::model::RepoError::GitError {
+        message: "push job failed".to_string(),
+    })?
+}
+
+#[tauri::command]
+pub async fn repo_log(
+    req: RepoLogRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<LogPage, String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    tauri::async_runtime::spawn_blocking(move || git::log(&summary, &req))
+        .await
+        .map_err(|_| "log job failed".to_string())?
+}
+
+#[tauri::command]
+pub async fn repo_commit_detail(
+    req: RepoCommitDetailRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<CommitDetail, String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    tauri::async_runtime::spawn_blocking(move || git::commit_detail(&summary, &req.oid))
+        .await
+        .map_err(|_| "commit detail job failed".to_string())?
+}
+
+#[tauri::command]
+pub async fn repo_diff_range(
+    req: RepoRangeDiffRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<RangeDiff, String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    let summary_for_job = summary.clone();
+    let (resolved, cache_key) = tauri::async_runtime::spawn_blocking(move || {
+        let resolved = git::resolve_range(&summary_for_job, &req.from, req.to.as_deref())?;
+        let cache_key = git::range_diff_cache_key(&summary_for_job, &resolved);
+        Ok::<_, String>((resolved, cache_key))
+    })
+    .await
+    .map_err(|_| "range diff job failed".to_string())??;
+    // Range summaries share the diff cache, stored as JSON under their
+    // oid-based key.
+    if let Ok(guard) = state.lock() {
+        if let Some(cached) = guard.get_diff_cache(&cache_key) {
+            if let Ok(range) = serde_json::from_str::<RangeDiff>(&cached.text) {
+                return Ok(range);
+            }
+        }
+    }
+
+    // Diff the commits the key was built from, not a fresh resolution.
+    let range = tauri::async_runtime::spawn_blocking(move || git::range_diff(&summary, &resolved))
+        .await
+        .map_err(|_| "range diff job failed".to_string())??;
+    let text = serde_json::to_string(&range).map_err(|e| e.to_string())?;
+    let mut guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+    guard.set_diff_cache(cache_key, UnifiedDiffText { text });
+    Ok(range)
+}
+
+#[tauri::command]
+pub async fn repo_blame(
+    req: RepoBlameRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<BlameResult, String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    tauri::async_runtime::spawn_blocking(move || {
+        git::blame(
+            &summary,
+            &req.path,
+            req.revision.as_deref(),
+            req.working_tree,
+        )
+    })
+    .await
+    .map_err(|_| "blame job failed".to_string())?
+}
+
+#[tauri::command]
+pub async fn repo_file_history(
+    req: RepoFileHistoryRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<FileHistoryPage, String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    tauri::async_runtime::spawn_blocking(move || {
+        git::file_history(
+            &summary,
+            &req.path,
+            req.revision.as_deref(),
+            req.cursor.as_deref(),
+            req.limit,
+        )
+    })
+    .await
+    .map_err(|_| "file history job failed".to_string())?
+}
+
+#[tauri::command]
+pub async fn repo_operation_continue(
+    req: RepoStatusRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), crate::model::RepoError> {
+    run_repo_operation(req, state, git::operation_continue, "continue").await
+}
+
+#[tauri::command]
+pub async fn repo_operation_abort(
+    req: RepoStatusRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), crate::model::RepoError> {
+    run_repo_operation(req, state, git::operation_abort, "abort").await
+}
+
+#[tauri::command]
+pub async fn repo_operation_skip(
+    req: RepoStatusRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), crate::model::RepoError> {
+    run_repo_operation(req, state, git::operation_skip, "skip").await
+}
+
+async fn run_repo_operation(
+    req: RepoStatusRequest,
+    state: State<'_, Mutex<AppState>>,
+    operation: fn(&RepoSummary) -> Result<(), crate::model::RepoError>,
+    label: &'static str,
+) -> Result<(), crate::model::RepoError> {
+    let summary = {
+        let guard = state
+            .lock()
+            .map_err(|_| crate::model::RepoError::GitError {
+                message: "state lock failed".to_string(),
+            })?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| crate::model::RepoError::GitError {
+        message: "unknown repo id".to_string(),
+    })?;
+
+    let summary_for_job = summary.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || operation(&summary_for_job))
         .await
-        .map_err(|_| "worktree list failed".to_string())?
-}
+        .map_err(|_| crate::model::RepoError::GitError {
+            message: format!("{label} job failed"),
+        })?;
 
-#[tauri::command]
-pub async fn wt_add(req: WorktreeAddRequest) -> Result<WorktreeResult, String> {
-    tauri::async_runtime::spawn_blocking(move || {
-        git::add_worktree(&req.repo_root, &req.path, &req.branch_name, req.new_branch)
-    })
-    .await
-    .map_err(|_| "worktree add failed".to_string())?
+    // Refresh even on failure; a step that stops on conflicts changes the tree.
+    if let Err(error) = refresh_cached_status(&summary, &state) {
+        tracing::warn!(
+            repo_id = %summary.repo_id,
+            error = %error,
+            operation = label,
+            "failed to refresh cached status after repository operation"
+        );
+    }
+    result
 }
 
 #[tauri::command]
-pub async fn wt_remove(req: WorktreePathRequest) -> Result<WorktreeResult, String> {
-    tauri::async_runtime::spawn_blocking(move || git::remove_worktree(&req.repo_root, &req.path))
-        .await
-        .map_err(|_| "worktree remove failed".to_string())?
+pub async fn repo_revert(
+    req: RepoRevertRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<CommitApplyResult, crate::model::RepoError> {
+    run_commit_apply(
+        req.repo_id,
+        req.revision,
+        req.no_commit,
+        req.changelist_id,
+        state,
+        git::revert,
+        "revert",
+    )
+    .await
 }
 
 #[tauri::command]
-pub async fn wt_prune(req: WorktreePathRequest) -> Result<WorktreeResult, String> {
-    tauri::async_runtime::spawn_blocking(move || git::prune_worktrees(&req.repo_root))
-        .await
-        .map_err(|_| "worktree prune failed".to_string())?
+pub async fn repo_cherry_pick(
+    req: RepoCherryPickRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<CommitApplyResult, crate::model::RepoError> {
+    run_commit_apply(
+        req.repo_id,
+        req.revision,
+        req.no_commit,
+        req.changelist_id,
+        state,
+        git::cherry_pick,
+        "cherry-pick",
+    )
+    .await
 }
 
-#[tauri::command]
-pub async fn repo_stage(
-    req: RepoPathRequest,
+/// Shared body of `repo_revert` and `repo_cherry_pick`. A no-commit result
+/// is assigned to `changelist_id`, which is validated before touching the
+/// working tree.
+async fn run_commit_apply(
+    repo_id: String,
+    revision: String,
+    no_commit: bool,
+    changelist_id: Option<String>,
     state: State<'_, Mutex<AppState>>,
-) -> Result<(), String> {
+    apply: fn(&RepoSummary, &str, bool) -> Result<CommitApplyResult, crate::model::RepoError>,
+    label: &'static str,
+) -> Result<CommitApplyResult, crate::model::RepoError> {
+    let git_error = |message: String| crate::model::RepoError::GitError { message };
     let summary = {
-        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
-        guard.get_repo(&req.repo_id)
+        let guard = state
+            .lock()
+            .map_err(|_| git_error("state lock failed".to_string()))?;
+        guard.get_repo(&repo_id)
     };
-    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
-    git::stage_path(&summary, &req.path)?;
+    let summary = summary.ok_or_else(|| git_error("unknown repo id".to_string()))?;
+    let changelist_id = changelist_id.filter(|_| no_commit);
+
+    let summary_for_job = summary.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        if let Some(id) = changelist_id.as_deref() {
+            let cl_state = changelist::load_state(&summary_for_job).map_err(git_error)?;
+            if !cl_state.lists.iter().any(|item| item.id == id) {
+                return Err(git_error("unknown changelist id".to_string()));
+            }
+        }
+        let result = apply(&summary_for_job, &revision, no_commit)?;
+        if let Some(id) = changelist_id.as_deref() {
+            changelist::assign_files(&summary_for_job, id, &result.paths).map_err(git_error)?;
+        }
+        Ok(result)
+    })
+    .await
+    .map_err(|_| git_error(format!("{label} job failed")))?;
+
+    // Refresh even on failure; a step that stops on conflicts changes the tree.
     if let Err(error) = refresh_cached_status(&summary, &state) {
         tracing::warn!(
             repo_id = %summary.repo_id,
-            path = %req.path,
             error = %error,
-            "failed to refresh cached status after stage"
+            operation = label,
+            "failed to refresh cached status after repository operation"
         );
     }
-    Ok(())
+    result
 }
 
 #[tauri::command]
-pub async fn repo_track(
-    req: RepoPathRequest,
+pub async fn repo_reset(
+    req: RepoResetRequest,
     state: State<'_, Mutex<AppState>>,
-) -> Result<(), String> {
-    let summary = {
-        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
-        guard.get_repo(&req.repo_id)
+) -> Result<ResetResult, crate::model::RepoError> {
+    let (summary, expected_head) = {
+        let mut guard = state
+            .lock()
+            .map_err(|_| crate::model::RepoError::GitError {
+                message: "state lock failed".to_string(),
+            })?;
+        let expected_head = req.confirm.as_deref().and_then(|nonce| {
+            guard.take_reset_confirmation(&req.repo_id, &req.revision, nonce)
+        });
+        (guard.get_repo(&req.repo_id), expected_head)
     };
-    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
-    git::track_path(&summary, &req.path)?;
+    let summary = summary.ok_or_else(|| crate::model::RepoError::GitError {
+        message: "unknown repo id".to_string(),
+    })?;
+
+    let summary_for_job = summary.clone();
+    let revision = req.revision.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        git::reset(&summary_for_job, &revision, req.mode, expected_head.as_deref())
+    })
+    .await
+    .map_err(|_| crate::model::RepoError::GitError {
+        message: "reset job failed".to_string(),
+    })?;
+    // The git layer reports the HEAD it checked; the client only ever sees
+    // a one-time nonce bound to this repo and target.
+    let result = match result {
+        Err(crate::model::RepoError::ConfirmationRequired { message, token }) => {
+            let mut guard = state
+                .lock()
+                .map_err(|_| crate::model::RepoError::GitError {
+                    message: "state lock failed".to_string(),
+                })?;
+            let nonce = guard.issue_reset_confirmation(&req.repo_id, &req.revision, token);
+            return Err(crate::model::RepoError::ConfirmationRequired {
+                message,
+                token: nonce,
+            });
+        }
+        result => result?,
+    };
+
     if let Err(error) = refresh_cached_status(&summary, &state) {
         tracing::warn!(
             repo_id = %summary.repo_id,
-            path = %req.path,
             error = %error,
-            "failed to refresh cached status after track"
+            "failed to refresh cached status after reset"
         );
     }
-    Ok(())
+    Ok(result)
 }
 
 #[tauri::command]
-pub async fn repo_unstage(
-    req: RepoPathRequest,
+pub async fn stash_list(
+    req: RepoStatusRequest,
     state: State<'_, Mutex<AppState>>,
-) -> Result<(), String> {
+) -> Result<Vec<StashEntry>, String> {
     let summary = {
         let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
         guard.get_repo(&req.repo_id)
     };
     let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
-    git::unstage_path(&summary, &req.path)?;
+    tauri::async_runtime::spawn_blocking(move || git::stash_list(&summary))
+        .await
+        .map_err(|_| "stash list job failed".to_string())?
+}
+
+#[tauri::command]
+pub async fn stash_save(
+    req: StashSaveRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<StashEntry, String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    let summary_for_job = summary.clone();
+    let entry = tauri::async_runtime::spawn_blocking(move || {
+        git::stash_save(
+            &summary_for_job,
+            req.message.as_deref(),
+            req.include_untracked,
+            req.keep_index,
+        )
+    })
+    .await
+    .map_err(|_| "stash job failed".to_string())??;
+
     if let Err(error) = refresh_cached_status(&summary, &state) {
         tracing::warn!(
             repo_id = %summary.repo_id,
-            path = %req.path,
             error = %error,
-            "failed to refresh cached status after unstage"
+            "failed to refresh cached status after stash"
         );
     }
-    Ok(())
+    Ok(entry)
 }
 
 #[tauri::command]
-pub async fn repo_branches(
-    req: RepoBranchListRequest,
+pub async fn stash_apply(
+    req: StashEntryRequest,
     state: State<'_, Mutex<AppState>>,
-) -> Result<BranchList, String> {
-    let summary = {
-        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
-        guard.get_repo(&req.repo_id)
-    };
-    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
-    git::list_branches(&summary)
+) -> Result<(), crate::model::RepoError> {
+    run_stash_apply(req, state, git::stash_apply, "stash apply").await
 }
 
 #[tauri::command]
-pub async fn repo_checkout(
-    req: RepoCheckoutRequest,
+pub async fn stash_pop(
+    req: StashEntryRequest,
     state: State<'_, Mutex<AppState>>,
-) -> Result<CheckoutResult, crate::model::RepoError> {
+) -> Result<(), crate::model::RepoError> {
+    run_stash_apply(req, state, git::stash_pop, "stash pop").await
+}
+
+async fn run_stash_apply(
+    req: StashEntryRequest,
+    state: State<'_, Mutex<AppState>>,
+    apply: fn(&RepoSummary, u32, &str) -> Result<(), crate::model::RepoError>,
+    label: &'static str,
+) -> Result<(), crate::model::RepoError> {
     let summary = {
         let guard = state
             .lock()
@@ -371,54 +1249,53 @@ pub async fn repo_checkout(
     })?;
 
     let summary_for_job = summary.clone();
-    let result = tauri::async_runtime::spawn_blocking(move || {
-        git::checkout_branch(&summary_for_job, &req.target)
-    })
-        .await
-        .map_err(|_| crate::model::RepoError::GitError {
-            message: "checkout job failed".to_string(),
-        })??;
+    let result =
+        tauri::async_runtime::spawn_blocking(move || apply(&summary_for_job, req.index, &req.oid))
+            .await
+            .map_err(|_| crate::model::RepoError::GitError {
+                message: format!("{label} job failed"),
+            })?;
 
-    // Update cached status after checkout so UI refreshes quickly.
-    if let Ok(mut status) = git::status(&summary) {
-        if let Ok(mut cl_state) = changelist::load_state(&summary) {
-            let _ = changelist::apply_to_status(&summary, &mut cl_state, &mut status);
-        }
-        if let Ok(mut guard) = state.lock() {
-            guard.set_status(status);
-        }
+    // Refresh even on conflicts so the UI shows the conflicted files.
+    if let Err(error) = refresh_cached_status(&summary, &state) {
+        tracing::warn!(
+            repo_id = %summary.repo_id,
+            error = %error,
+            operation = label,
+            "failed to refresh cached status after stash apply"
+        );
     }
-
-    Ok(result)
+    result
 }
 
 #[tauri::command]
-pub async fn repo_create_branch(
-    req: RepoCreateBranchRequest,
+pub async fn stash_drop(
+    req: StashEntryRequest,
     state: State<'_, Mutex<AppState>>,
-) -> Result<BranchCreateResult, String> {
+) -> Result<(), String> {
     let summary = {
         let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
         guard.get_repo(&req.repo_id)
     };
     let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
-    git::create_branch(&summary, &req.name, req.from.as_deref())?;
-    Ok(BranchCreateResult { name: req.name })
+    tauri::async_runtime::spawn_blocking(move || git::stash_drop(&summary, req.index, &req.oid))
+        .await
+        .map_err(|_| "stash drop job failed".to_string())?
 }
 
 #[tauri::command]
-pub async fn repo_fetch(
-    req: RepoFetchRequest,
+pub async fn stash_show(
+    req: StashIndexRequest,
     state: State<'_, Mutex<AppState>>,
-) -> Result<crate::model::FetchResult, String> {
+) -> Result<StashDetail, String> {
     let summary = {
         let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
         guard.get_repo(&req.repo_id)
     };
     let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
-    let remote = req.remote.clone().unwrap_or_else(|| "origin".to_string());
-    let updated = git::fetch(&summary, req.remote.as_deref())?;
-    Ok(crate::model::FetchResult { remote, updated })
+    tauri::async_runtime::spawn_blocking(move || git::stash_show(&summary, req.index))
+        .await
+        .map_err(|_| "stash show job failed".to_string())?
 }
 
 #[tauri::command]
@@ -462,6 +1339,19 @@ pub async fn cl_rename(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn cl_set_draft(
+    req: ChangelistDraftRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    changelist::set_draft(&summary, &req.id, &req.message)
+}
+
 #[tauri::command]
 pub async fn cl_delete(
     req: ChangelistIdRequest,
@@ -551,6 +1441,78 @@ pub async fn cl_unassign_hunks(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn cl_shelve(
+    req: ChangelistIdRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ShelfEntry, crate::model::RepoError> {
+    let summary = {
+        let guard = state
+            .lock()
+            .map_err(|_| crate::model::RepoError::GitError {
+                message: "state lock failed".to_string(),
+            })?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| crate::model::RepoError::GitError {
+        message: "unknown repo id".to_string(),
+    })?;
+    let summary_for_job = summary.clone();
+    let entry =
+        tauri::async_runtime::spawn_blocking(move || changelist::shelve(&summary_for_job, &req.id))
+            .await
+            .map_err(|_| crate::model::RepoError::GitError {
+                message: "shelve job failed".to_string(),
+            })??;
+    if let Err(error) = refresh_cached_status(&summary, &state) {
+        tracing::warn!(
+            repo_id = %summary.repo_id,
+            error = %error,
+            "failed to refresh cached status after shelve"
+        );
+    }
+    Ok(entry)
+}
+
+#[tauri::command]
+pub async fn cl_unshelve(
+    req: ShelfIdRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<UnshelveResult, String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    let summary_for_job = summary.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        changelist::unshelve(&summary_for_job, &req.shelf_id)
+    })
+    .await
+    .map_err(|_| "unshelve job failed".to_string())??;
+    if let Err(error) = refresh_cached_status(&summary, &state) {
+        tracing::warn!(
+            repo_id = %summary.repo_id,
+            error = %error,
+            "failed to refresh cached status after unshelve"
+        );
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn cl_shelf_list(
+    req: RepoStatusRequest,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<ShelfEntry>, String> {
+    let summary = {
+        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        guard.get_repo(&req.repo_id)
+    };
+    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    changelist::list_shelf(&summary)
+}
+
 #[tauri::command]
 pub async fn commit_prepare(
     req: CommitPrepareRequest,
@@ -561,28 +1523,56 @@ pub async fn commit_prepare(
         guard.get_repo(&req.repo_id)
     };
     let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
-    build_commit_preview(&summary, &req.changelist_id)
+    let mut preview = build_commit_preview(&summary, &req.changelist_id)?;
+    if let Some(message) = req.message.as_deref() {
+        let (config, error) = load_lint_config(&summary);
+        if let Some(error) = error {
+            preview.warnings.push(preview_warning(error));
+        }
+        preview
+            .warnings
+            .extend(lint::lint_message(&config, message));
+    }
+    Ok(preview)
 }
 
 #[tauri::command]
 pub async fn commit_execute(
     req: CommitExecuteRequest,
     state: State<'_, Mutex<AppState>>,
-) -> Result<CommitResult, String> {
+) -> Result<CommitResult, crate::model::RepoError> {
+    let git_error = |message: String| crate::model::RepoError::GitError { message };
     let summary = {
-        let guard = state.lock().map_err(|_| "state lock failed".to_string())?;
+        let guard = state
+            .lock()
+            .map_err(|_| git_error("state lock failed".to_string()))?;
         guard.get_repo(&req.repo_id)
     };
-    let summary = summary.ok_or_else(|| "unknown repo id".to_string())?;
+    let summary = summary.ok_or_else(|| git_error("unknown repo id".to_string()))?;
 
-    let preview = build_commit_preview(&summary, &req.changelist_id)?;
+    let preview = build_commit_preview(&summary, &req.changelist_id).map_err(git_error)?;
     if !preview.invalid_hunks.is_empty() {
-        return Err("Some hunks need reselect before committing.".to_string());
+        return Err(git_error(
+            "Some hunks need reselect before committing.".to_string(),
+        ));
+    }
+    // Keep the typed message if the commit fails or the app goes away.
+    changelist::set_draft(&summary, &req.changelist_id, &req.message).map_err(git_error)?;
+    let (lint_config, _) = load_lint_config(&summary);
+    let violations: Vec<_> = lint::lint_message(&lint_config, &req.message)
+        .into_iter()
+        .filter(|warning| warning.severity == Severity::Error)
+        .collect();
+    if !violations.is_empty() {
+        return Err(crate::model::RepoError::CommitLintFailed {
+            message: "Commit message does not follow the repository's commit rules.".to_string(),
+            violations,
+        });
     }
     let options = req.options;
     let message = req.message.clone();
     let files = preview.files.clone();
-    let hunk_files = collect_hunk_files(&summary, &req.changelist_id)?;
+    let hunk_files = collect_hunk_files(&summary, &req.changelist_id).map_err(git_error)?;
     let summary_for_job = summary.clone();
     let result = tauri::async_runtime::spawn_blocking(move || {
         if hunk_files.is_empty() {
@@ -603,9 +1593,9 @@ pub async fn commit_execute(
         }
     })
     .await
-    .map_err(|_| "commit job failed".to_string())??;
+    .map_err(|_| git_error("commit job failed".to_string()))??;
 
-    let mut status = git::status(&summary)?;
+    let mut status = git::status(&summary).map_err(git_error)?;
     if let Ok(mut cl_state) = changelist::load_state(&summary) {
         let _ = changelist::apply_to_status(&summary, &mut cl_state, &mut status);
     }
@@ -618,11 +1608,19 @@ pub async fn commit_execute(
         .filter(|path| !dirty_paths.contains(*path))
         .cloned()
         .collect();
-    changelist::clear_assignments(&summary, &clean_paths)?;
+    changelist::clear_assignments(&summary, &clean_paths).map_err(git_error)?;
+    // The commit already landed; a lost history entry must not report it as failed.
+    if let Err(error) = changelist::record_message(&summary, &req.changelist_id, &req.message) {
+        tracing::warn!(
+            repo_id = %summary.repo_id,
+            error = %error,
+            "failed to record commit message history"
+        );
+    }
     if let Ok(mut guard) = state.lock() {
         guard.set_status(status);
     }
-    update_cached_changelists(&summary, &state)?;
+    update_cached_changelists(&summary, &state).map_err(git_error)?;
 
     Ok(result)
 }
@@ -666,6 +1664,8 @@ fn build_commit_preview(
         return Err("unknown changelist id".to_string());
     }
     let _ = changelist::apply_to_status(summary, &mut cl_state, &mut status);
+    // Re-anchor a copy only; committing saves it in `collect_hunk_files`.
+    changelist::reanchor_hunks(summary, &mut cl_state)?;
 
     let files: Vec<_> = status
         .files
@@ -738,18 +1738,28 @@ fn preview_from_files(
                 )
             {
                 invalid_hunks.extend(assignment.hunks.clone());
-                warnings.push(
+                warnings.push(preview_warning(
                     "Unstaged hunks cannot be committed while staged changes exist in the same file."
                         .to_string(),
-                );
+                ));
                 continue;
             }
         }
 
+        // One diff per kind for the file, shared by all of its assignments.
+        let mut current: Vec<(crate::model::RepoDiffKind, Vec<DiffHunk>)> = Vec::new();
         let mut invalid_for_file = Vec::new();
         for hunk in &assignment.hunks {
-            let hunks = git::diff_hunks_for_path(summary, path, hunk.kind.clone())?;
-            let found = hunks
+            let index = match current.iter().position(|(kind, _)| *kind == hunk.kind) {
+                Some(index) => index,
+                None => {
+                    let hunks = git::diff_hunks_for_path(summary, path, hunk.kind.clone())?;
+                    current.push((hunk.kind.clone(), hunks));
+                    current.len() - 1
+                }
+            };
+            let found = current[index]
+                .1
                 .iter()
                 .any(|diff| diff.id == hunk.id && diff.content_hash == hunk.content_hash);
             if !found {
@@ -758,14 +1768,16 @@ fn preview_from_files(
         }
         if !invalid_for_file.is_empty() {
             invalid_hunks.extend(invalid_for_file);
-            warnings.push("Some hunks no longer match the file. Reselect required.".to_string());
+            warnings.push(preview_warning(
+                "Some hunks no longer match the file. Reselect required.".to_string(),
+            ));
         }
     }
 
     if has_mixed {
-        warnings.push(
+        warnings.push(preview_warning(
             "Some files have both staged and unstaged changes; the commit will use the working tree version.".to_string(),
-        );
+        ));
     }
 
     Ok(CommitPreview {
@@ -778,11 +1790,37 @@ fn preview_from_files(
     })
 }
 
+// A broken rules file is reported but never blocks committing.
+fn load_lint_config(summary: &RepoSummary) -> (CommitLintConfig, Option<String>) {
+    match lint::load_config(summary) {
+        Ok(config) => (config, None),
+        Err(error) => {
+            tracing::warn!(
+                repo_id = %summary.repo_id,
+                error = %error,
+                "failed to load commit lint config; using defaults"
+            );
+            (CommitLintConfig::default(), Some(error))
+        }
+    }
+}
+
+fn preview_warning(message: String) -> CommitWarning {
+    CommitWarning {
+        severity: Severity::Warning,
+        message,
+        rule: None,
+    }
+}
+
 fn collect_hunk_files(
     summary: &RepoSummary,
     changelist_id: &str,
 ) -> Result<Vec<(String, Vec<HunkAssignment>)>, String> {
-    let state = changelist::load_state(summary)?;
+    let mut state = changelist::load_state(summary)?;
+    if changelist::reanchor_hunks(summary, &mut state)? {
+        changelist::save_state(summary, &state)?;
+    }
     let mut result = Vec::new();
     for (path, assignment) in state.hunk_assignments {
         if assignment.changelist_id != changelist_id {
@@ -807,7 +1845,7 @@ fn normalize_repo_path(path: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::preview_from_files;
+    use super::{load_lint_config, preview_from_files};
     use crate::model::{HunkAssignmentSet, RepoSummary, StatusFile, StatusKind};
 
     #[test]
@@ -879,4 +1917,27 @@ mod tests {
         assert_eq!(preview.stats.staged, 1);
         assert_eq!(preview.stats.untracked, 1);
     }
+
+    #[test]
+    fn broken_lint_config_falls_back_to_defaults() {
+        let root = std::env::temp_dir().join(format!("gitpanel-lint-{}", crate::store::now_ms()));
+        std::fs::create_dir_all(root.join(".gitpanel")).expect("create config dir");
+        std::fs::write(root.join(".gitpanel").join("commit-lint.json"), "{ nope")
+            .expect("write config");
+        let root_path = root.to_string_lossy().to_string();
+        let summary = RepoSummary {
+            repo_id: "test".to_string(),
+            path: root_path.clone(),
+            name: "test".to_string(),
+            repo_root: root_path.clone(),
+            worktree_path: root_path,
+            is_valid: true,
+        };
+
+        let (config, error) = load_lint_config(&summary);
+        assert!(error.is_some());
+        assert!(config.summary_length.is_some());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
 }
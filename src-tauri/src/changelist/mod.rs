@@ -1,20 +1,25 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 
-use crate::git::resolve_git_dir;
+use crate::git::{self, resolve_git_dir};
 use crate::model::{
-    Changelist, ChangelistState, HunkAssignment, HunkAssignmentSet, RepoStatus, RepoSummary,
+    Changelist, ChangelistState, DiffHunk, HunkAssignment, HunkAssignmentSet, HunkLineRange,
+    RepoDiffKind, RepoError, RepoStatus, RepoSummary, ShelfEntry, StatusKind, UnshelveResult,
 };
 use crate::store::now_ms;
 
 const DEFAULT_ID: &str = "default";
 const DEFAULT_NAME: &str = "Default";
+const REANCHOR_MIN_SIMILARITY: f64 = 0.6;
+/// Furthest a hunk may move, in lines, and still be re-anchored.
+const REANCHOR_MAX_DISTANCE: u32 = 200;
+const MESSAGE_HISTORY_LIMIT: usize = 20;
 
 pub fn load_state(summary: &RepoSummary) -> Result<ChangelistState, String> {
     let path = changelist_path(summary);
     if !path.exists() {
-        let state = default_state();
+        let state = seeded_default_state(summary);
         save_state(summary, &state)?;
         return Ok(state);
     }
@@ -23,7 +28,7 @@ pub fn load_state(summary: &RepoSummary) -> Result<ChangelistState, String> {
     let mut state: ChangelistState = match serde_json::from_str(&content) {
         Ok(state) => state,
         Err(_) => {
-            let state = default_state();
+            let state = seeded_default_state(summary);
             save_state(summary, &state)?;
             return Ok(state);
         }
@@ -54,6 +59,7 @@ pub fn create(summary: &RepoSummary, name: &str) -> Result<Changelist, String> {
         id: id.clone(),
         name: name.to_string(),
         created_at: now_ms(),
+        draft: git::commit_template(summary),
     };
     state.lists.push(list.clone());
     save_state(summary, &state)?;
@@ -94,6 +100,35 @@ pub fn delete(summary: &RepoSummary, id: &str) -> Result<(), String> {
     Ok(())
 }
 
+pub fn set_draft(summary: &RepoSummary, id: &str, message: &str) -> Result<(), String> {
+    let mut state = load_state(summary)?;
+    let list = state
+        .lists
+        .iter_mut()
+        .find(|item| item.id == id)
+        .ok_or_else(|| "unknown changelist id".to_string())?;
+    list.draft = Some(message.to_string());
+    save_state(summary, &state)?;
+    Ok(())
+}
+
+/// Moves a committed message into the history and starts a fresh draft
+/// for the changelist.
+pub fn record_message(summary: &RepoSummary, id: &str, message: &str) -> Result<(), String> {
+    let mut state = load_state(summary)?;
+    let message = message.trim();
+    if !message.is_empty() {
+        state.message_history.retain(|item| item != message);
+        state.message_history.insert(0, message.to_string());
+        state.message_history.truncate(MESSAGE_HISTORY_LIMIT);
+    }
+    if let Some(list) = state.lists.iter_mut().find(|item| item.id == id) {
+        list.draft = git::commit_template(summary);
+    }
+    save_state(summary, &state)?;
+    Ok(())
+}
+
 pub fn set_active(summary: &RepoSummary, id: &str) -> Result<(), String> {
     let mut state = load_state(summary)?;
     if !state.lists.iter().any(|item| item.id == id) {
@@ -154,6 +189,38 @@ pub fn assign_hunks(
     if hunks.is_empty() {
         return Err("no hunks provided".to_string());
     }
+    let has_bad_range = hunks
+        .iter()
+        .filter_map(|hunk| hunk.line_ranges.as_ref())
+        .any(|ranges| ranges.is_empty() || ranges.iter().any(|range| range.start > range.end));
+    if has_bad_range {
+        return Err("invalid line range".to_string());
+    }
+    let has_history_hunk = hunks.iter().any(|hunk| {
+        matches!(
+            hunk.kind,
+            RepoDiffKind::Commit { .. } | RepoDiffKind::Range { .. }
+        )
+    });
+    if has_history_hunk {
+        return Err("committed hunks cannot be assigned to a changelist".to_string());
+    }
+    // Keep the body from the current diff so later edits can be re-anchored;
+    // a client-supplied body is never trusted.
+    let mut hunks = hunks.to_vec();
+    for kind in [RepoDiffKind::Unstaged, RepoDiffKind::Staged] {
+        if !hunks.iter().any(|hunk| hunk.kind == kind) {
+            continue;
+        }
+        let current = git::diff_hunks_for_path(summary, path, kind.clone())?;
+        for hunk in hunks.iter_mut().filter(|hunk| hunk.kind == kind) {
+            let diff = current
+                .iter()
+                .find(|diff| diff.id == hunk.id && diff.content_hash == hunk.content_hash)
+                .ok_or_else(|| "hunk no longer matches the diff; refresh and retry".to_string())?;
+            hunk.content = Some(diff.content.clone());
+        }
+    }
     let mut state = load_state(summary)?;
     if !state.lists.iter().any(|item| item.id == changelist_id) {
         return Err("unknown changelist id".to_string());
@@ -163,7 +230,7 @@ pub fn assign_hunks(
         path.to_string(),
         HunkAssignmentSet {
             changelist_id: changelist_id.to_string(),
-            hunks: hunks.to_vec(),
+            hunks,
         },
     );
     save_state(summary, &state)?;
@@ -186,6 +253,202 @@ pub fn unassign_hunks(
     Ok(())
 }
 
+/// Moves stored hunk assignments onto the current diff hunks when edits
+/// elsewhere in the file shifted their position or lightly touched their
+/// content. Assignments that cannot be matched are left untouched so the
+/// commit preview still reports them as invalid. Only `state` is updated;
+/// callers save it when this returns `true`.
+pub fn reanchor_hunks(summary: &RepoSummary, state: &mut ChangelistState) -> Result<bool, String> {
+    let mut changed = false;
+    for (path, assignment) in state.hunk_assignments.iter_mut() {
+        let mut updated = Vec::with_capacity(assignment.hunks.len());
+        for kind in [RepoDiffKind::Unstaged, RepoDiffKind::Staged] {
+            let stored: Vec<HunkAssignment> = assignment
+                .hunks
+                .iter()
+                .filter(|hunk| hunk.kind == kind)
+                .cloned()
+                .collect();
+            if stored.is_empty() {
+                continue;
+            }
+            let current = git::diff_hunks_for_path(summary, path, kind)?;
+            let (moved, moved_any) = reanchor_assignments(&stored, &current);
+            changed |= moved_any;
+            updated.extend(moved);
+        }
+        assignment.hunks = updated;
+    }
+    Ok(changed)
+}
+
+pub fn reanchor_assignments(
+    stored: &[HunkAssignment],
+    current: &[DiffHunk],
+) -> (Vec<HunkAssignment>, bool) {
+    let mut claimed = vec![false; current.len()];
+    let mut result: Vec<Option<HunkAssignment>> = vec![None; stored.len()];
+
+    for (index, hunk) in stored.iter().enumerate() {
+        if let Some(pos) = current
+            .iter()
+            .position(|diff| diff.id == hunk.id && diff.content_hash == hunk.content_hash)
+        {
+            claimed[pos] = true;
+            result[index] = Some(hunk.clone());
+        }
+    }
+
+    let mut changed = false;
+    for (index, hunk) in stored.iter().enumerate() {
+        if result[index].is_some() {
+            continue;
+        }
+        let mut best: Option<(usize, f64, u32)> = None;
+        for (pos, diff) in current.iter().enumerate() {
+            if claimed[pos] {
+                continue;
+            }
+            let similarity = if diff.content_hash == hunk.content_hash {
+                1.0
+            } else if let Some(content) = hunk.content.as_deref() {
+                change_similarity(content, &diff.content)
+            } else {
+                0.0
+            };
+            if similarity < REANCHOR_MIN_SIMILARITY {
+                continue;
+            }
+            let distance = diff.old_start.abs_diff(hunk.old_start);
+            if distance > REANCHOR_MAX_DISTANCE {
+                continue;
+            }
+            let score =
+                similarity * (1.0 - f64::from(distance) / f64::from(REANCHOR_MAX_DISTANCE + 1));
+            let better = match best {
+                None => true,
+                Some((_, best_score, best_distance)) => {
+                    score > best_score || (score == best_score && distance < best_distance)
+                }
+            };
+            if better {
+                best = Some((pos, score, distance));
+            }
+        }
+
+        let Some((pos, _, _)) = best else {
+            result[index] = Some(hunk.clone());
+            continue;
+        };
+        let diff = &current[pos];
+        // A fuzzy match may carry changes the user never assigned, so only
+        // the originally assigned change lines move to the new hunk.
+        let line_ranges = match (hunk.line_ranges.as_deref(), hunk.content.as_deref()) {
+            (None, _) if diff.content_hash == hunk.content_hash => None,
+            (ranges, old_content) => {
+                let old_content = old_content.unwrap_or(&diff.content);
+                let whole = [HunkLineRange {
+                    start: 0,
+                    end: old_content.lines().count().saturating_sub(1) as u32,
+                }];
+                match remap_line_ranges(old_content, &diff.content, ranges.unwrap_or(&whole[..])) {
+                    Some(ranges) => Some(ranges),
+                    None => {
+                        result[index] = Some(hunk.clone());
+                        continue;
+                    }
+                }
+            }
+        };
+        claimed[pos] = true;
+        changed = true;
+        result[index] = Some(HunkAssignment {
+            id: diff.id.clone(),
+            header: diff.header.clone(),
+            old_start: diff.old_start,
+            old_lines: diff.old_lines,
+            new_start: diff.new_start,
+            new_lines: diff.new_lines,
+            content_hash: diff.content_hash.clone(),
+            kind: hunk.kind.clone(),
+            line_ranges,
+            content: hunk.content.as_ref().map(|_| diff.content.clone()),
+        });
+    }
+
+    (result.into_iter().flatten().collect(), changed)
+}
+
+fn changed_lines(content: &str) -> Vec<&str> {
+    content
+        .lines()
+        .filter(|line| line.starts_with('+') || line.starts_with('-'))
+        .collect()
+}
+
+/// Dice coefficient over the added/removed lines of two hunk bodies.
+fn change_similarity(left: &str, right: &str) -> f64 {
+    let left = changed_lines(left);
+    let right = changed_lines(right);
+    if left.is_empty() || right.is_empty() {
+        return 0.0;
+    }
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for line in &left {
+        *counts.entry(line).or_insert(0) += 1;
+    }
+    let mut common = 0;
+    for line in &right {
+        if let Some(count) = counts.get_mut(line) {
+            if *count > 0 {
+                *count -= 1;
+                common += 1;
+            }
+        }
+    }
+    (2 * common) as f64 / (left.len() + right.len()) as f64
+}
+
+/// Maps selected lines of the old hunk body onto identical lines of the new
+/// one, in order. Returns `None` if any selected line no longer exists.
+fn remap_line_ranges(
+    old_content: &str,
+    new_content: &str,
+    ranges: &[HunkLineRange],
+) -> Option<Vec<HunkLineRange>> {
+    let new_lines: Vec<&str> = new_content.lines().collect();
+    let mut next = 0;
+    let mut mapped: Vec<u32> = Vec::new();
+    for (index, line) in old_content.lines().enumerate() {
+        let index = index as u32;
+        if !ranges.iter().any(|range| range.start <= index && index <= range.end) {
+            continue;
+        }
+        if !(line.starts_with('+') || line.starts_with('-')) {
+            continue;
+        }
+        let offset = new_lines[next..].iter().position(|candidate| *candidate == line)?;
+        let found = next + offset;
+        mapped.push(found as u32);
+        next = found + 1;
+    }
+    if mapped.is_empty() {
+        return None;
+    }
+
+    let mut result: Vec<HunkLineRange> = Vec::new();
+    for index in mapped {
+        match result.last_mut() {
+            Some(range) if range.end + 1 == index => range.end = index,
+            _ => result.push(HunkLineRange {
+                start: index,
+                end: index,
+            }),
+        }
+    }
+    Some(result)
+}
+
 pub fn apply_to_status(
     summary: &RepoSummary,
     state: &mut ChangelistState,
@@ -246,17 +509,242 @@ pub fn apply_to_status(
     Ok(())
 }
 
+/// Restores the assignments captured in `previous` after a checkout stashed
+/// and re-applied local changes. Files that came back keep their changelist;
+/// their hunks are re-anchored as unstaged changes because the stash is
+/// popped without `--index`. Files that did not come back are unassigned.
+pub fn restore_after_checkout(
+    summary: &RepoSummary,
+    previous: &ChangelistState,
+) -> Result<(), String> {
+    let status = git::status(summary)?;
+    let changed: HashSet<&str> = status.files.iter().map(|file| file.path.as_str()).collect();
+    let mut state = load_state(summary)?;
+    let known: HashSet<String> = state.lists.iter().map(|item| item.id.clone()).collect();
+
+    for (path, id) in &previous.assignments {
+        if changed.contains(path.as_str()) && known.contains(id) {
+            state.assignments.insert(path.clone(), id.clone());
+        } else {
+            state.assignments.remove(path);
+        }
+    }
+    for (path, assignment) in &previous.hunk_assignments {
+        if !changed.contains(path.as_str()) || !known.contains(&assignment.changelist_id) {
+            state.hunk_assignments.remove(path);
+            continue;
+        }
+        let mut assignment = assignment.clone();
+        for hunk in &mut assignment.hunks {
+            hunk.kind = RepoDiffKind::Unstaged;
+        }
+        state.hunk_assignments.insert(path.clone(), assignment);
+    }
+    reanchor_hunks(summary, &mut state)?;
+    save_state(summary, &state)?;
+    Ok(())
+}
+
+/// Saves a changelist's changes under `.git/gitpanel/shelf/` and reverts
+/// them from the working tree. Files with both staged and unstaged changes
+/// are refused with `PartiallyStaged`: the shelved patch is taken against
+/// HEAD and could not be reverted cleanly from such a file.
+pub fn shelve(summary: &RepoSummary, changelist_id: &str) -> Result<ShelfEntry, RepoError> {
+    let git_error = |message: String| RepoError::GitError { message };
+    let mut state = load_state(summary).map_err(git_error)?;
+    let Some(list) = state.lists.iter().find(|item| item.id == changelist_id).cloned() else {
+        return Err(git_error("unknown changelist id".to_string()));
+    };
+    let mut status = git::status(summary).map_err(git_error)?;
+    apply_to_status(summary, &mut state, &mut status).map_err(git_error)?;
+
+    let mut partly_staged: Vec<String> = status
+        .files
+        .iter()
+        .filter(|file| file.changelist_id.as_deref() == Some(changelist_id))
+        .filter(|file| matches!(file.status, StatusKind::Both))
+        .map(|file| file.path.clone())
+        .collect();
+    if !partly_staged.is_empty() {
+        partly_staged.sort();
+        return Err(RepoError::PartiallyStaged {
+            message: "Files with both staged and unstaged changes cannot be shelved.".to_string(),
+            paths: partly_staged,
+        });
+    }
+
+    let full_files: Vec<_> = status
+        .files
+        .into_iter()
+        .filter(|file| file.changelist_id.as_deref() == Some(changelist_id))
+        .filter(|file| file.changelist_partial != Some(true))
+        .collect();
+    let hunk_files: Vec<(String, Vec<HunkAssignment>)> = state
+        .hunk_assignments
+        .iter()
+        .filter(|(_, assignment)| assignment.changelist_id == changelist_id)
+        .map(|(path, assignment)| (path.clone(), assignment.hunks.clone()))
+        .collect();
+    if full_files.is_empty() && hunk_files.is_empty() {
+        return Err(git_error("Changelist has no files.".to_string()));
+    }
+    if full_files
+        .iter()
+        .any(|file| matches!(file.status, StatusKind::Conflicted))
+    {
+        return Err(git_error("Changelist contains conflicted files.".to_string()));
+    }
+
+    let patch = git::changelist_patch(summary, &full_files, &hunk_files).map_err(git_error)?;
+    let mut id = format!("shelf-{}", now_ms());
+    if shelf_dir(summary).join(format!("{id}.json")).exists() {
+        id = format!("{id}-{}", list_shelf(summary).map_err(git_error)?.len());
+    }
+    let entry = ShelfEntry {
+        id: id.clone(),
+        changelist_id: list.id.clone(),
+        changelist_name: list.name.clone(),
+        created_at: now_ms(),
+        files: full_files.iter().map(|file| file.path.clone()).collect(),
+        hunk_assignments: hunk_files.iter().cloned().collect(),
+    };
+
+    let dir = shelf_dir(summary);
+    fs::create_dir_all(&dir).map_err(|e| git_error(e.to_string()))?;
+    fs::write(dir.join(format!("{id}.patch")), patch).map_err(|e| git_error(e.to_string()))?;
+    let meta = serde_json::to_string_pretty(&entry).map_err(|e| git_error(e.to_string()))?;
+    fs::write(dir.join(format!("{id}.json")), meta).map_err(|e| git_error(e.to_string()))?;
+
+    if let Err(error) = git::revert_changelist_changes(summary, &full_files, &hunk_files) {
+        let _ = fs::remove_file(dir.join(format!("{id}.patch")));
+        let _ = fs::remove_file(dir.join(format!("{id}.json")));
+        return Err(git_error(error));
+    }
+
+    for path in entry.files.iter().chain(entry.hunk_assignments.keys()) {
+        state.assignments.remove(path);
+        state.hunk_assignments.remove(path);
+    }
+    save_state(summary, &state).map_err(git_error)?;
+    Ok(entry)
+}
+
+/// Re-applies a shelved patch and restores its changelist assignments. The
+/// shelf entry is kept when the patch leaves conflicts behind.
+pub fn unshelve(summary: &RepoSummary, shelf_id: &str) -> Result<UnshelveResult, String> {
+    if !is_shelf_id(shelf_id) {
+        return Err("unknown shelf id".to_string());
+    }
+    let dir = shelf_dir(summary);
+    let meta_path = dir.join(format!("{shelf_id}.json"));
+    let patch_path = dir.join(format!("{shelf_id}.patch"));
+    let content = fs::read_to_string(&meta_path).map_err(|_| "unknown shelf id".to_string())?;
+    let entry: ShelfEntry = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+
+    let conflicts = git::apply_patch_file(summary, &patch_path)?;
+
+    let mut state = load_state(summary)?;
+    if !state.lists.iter().any(|item| item.id == entry.changelist_id) {
+        state.lists.push(Changelist {
+            id: entry.changelist_id.clone(),
+            name: entry.changelist_name.clone(),
+            created_at: now_ms(),
+            draft: git::commit_template(summary),
+        });
+    }
+    for path in &entry.files {
+        state.hunk_assignments.remove(path);
+        state
+            .assignments
+            .insert(path.clone(), entry.changelist_id.clone());
+    }
+    for (path, hunks) in &entry.hunk_assignments {
+        // The shelf patch is restored to the working tree only.
+        let hunks = hunks
+            .iter()
+            .cloned()
+            .map(|mut hunk| {
+                hunk.kind = RepoDiffKind::Unstaged;
+                hunk
+            })
+            .collect();
+        state.assignments.remove(path);
+        state.hunk_assignments.insert(
+            path.clone(),
+            HunkAssignmentSet {
+                changelist_id: entry.changelist_id.clone(),
+                hunks,
+            },
+        );
+    }
+    save_state(summary, &state)?;
+
+    if conflicts.is_empty() {
+        let _ = fs::remove_file(&patch_path);
+        let _ = fs::remove_file(&meta_path);
+    }
+    let mut restored_paths = entry.files.clone();
+    restored_paths.extend(entry.hunk_assignments.keys().cloned());
+    Ok(UnshelveResult {
+        shelf_id: entry.id,
+        changelist_id: entry.changelist_id,
+        restored_paths,
+        conflicts,
+    })
+}
+
+pub fn list_shelf(summary: &RepoSummary) -> Result<Vec<ShelfEntry>, String> {
+    let dir = shelf_dir(summary);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut entries = Vec::new();
+    for item in fs::read_dir(&dir).map_err(|e| e.to_string())?.flatten() {
+        let path = item.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Ok(entry) = serde_json::from_str::<ShelfEntry>(&content) {
+            entries.push(entry);
+        }
+    }
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.created_at));
+    Ok(entries)
+}
+
+/// Matches the `shelf-<millis>` and `shelf-<millis>-<n>` ids from `shelve`,
+/// so request ids cannot point outside the shelf directory.
+fn is_shelf_id(id: &str) -> bool {
+    id.strip_prefix("shelf-").is_some_and(|rest| {
+        rest.split('-')
+            .all(|part| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit()))
+    })
+}
+
 pub fn default_state() -> ChangelistState {
     ChangelistState {
         lists: vec![Changelist {
             id: DEFAULT_ID.to_string(),
             name: DEFAULT_NAME.to_string(),
             created_at: now_ms(),
+            draft: None,
         }],
         active_id: DEFAULT_ID.to_string(),
         assignments: HashMap::new(),
         hunk_assignments: HashMap::new(),
+        message_history: Vec::new(),
+    }
+}
+
+fn seeded_default_state(summary: &RepoSummary) -> ChangelistState {
+    let mut state = default_state();
+    for list in &mut state.lists {
+        list.draft = git::commit_template(summary);
     }
+    state
 }
 
 fn normalize_state(state: &mut ChangelistState) -> bool {
@@ -268,6 +756,7 @@ fn normalize_state(state: &mut ChangelistState) -> bool {
                 id: DEFAULT_ID.to_string(),
                 name: DEFAULT_NAME.to_string(),
                 created_at: now_ms(),
+                draft: None,
             },
         );
         changed = true;
@@ -295,20 +784,32 @@ fn changelist_path(summary: &RepoSummary) -> PathBuf {
     git_dir.join("gitpanel").join("changelists.json")
 }
 
+fn shelf_dir(summary: &RepoSummary) -> PathBuf {
+    let git_dir = resolve_git_dir(&summary.worktree_path);
+    git_dir.join("gitpanel").join("shelf")
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{assign_files, assign_hunks, create, load_state};
-    use crate::model::{HunkAssignment, RepoDiffKind, RepoSummary};
+    use super::{
+        assign_files, assign_hunks, create, list_shelf, load_state, reanchor_assignments,
+        reanchor_hunks, record_message, set_draft, shelve, unshelve, MESSAGE_HISTORY_LIMIT,
+    };
+    use crate::git;
+    use crate::model::{
+        CommitOptions, DiffHunk, HunkAssignment, HunkLineRange, RepoDiffKind, RepoError,
+        RepoSummary,
+    };
     use std::fs;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
     use std::time::{SystemTime, UNIX_EPOCH};
 
     fn temp_repo() -> (RepoSummary, PathBuf) {
-        let millis = SystemTime::now()
+        let nanos = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .expect("clock")
-            .as_millis();
-        let path = std::env::temp_dir().join(format!("gitpanel-test-{millis}"));
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("gitpanel-test-{nanos}"));
         fs::create_dir_all(path.join(".git")).expect("create repo dir");
         let summary = RepoSummary {
             repo_id: "test-repo".to_string(),
@@ -342,25 +843,301 @@ mod tests {
     #[test]
     fn persists_hunk_assignments() {
         let (summary, path) = temp_repo();
+        init_committed_repo(&path, "src/main.rs", "one\ntwo\n");
+        fs::write(path.join("src/main.rs"), "one\ntwo\nthree\n").expect("edit file");
 
         let created = create(&summary, "Feature").expect("create changelist");
-        let hunks = vec![HunkAssignment {
-            id: "1:1:1:1:deadbeef".to_string(),
-            header: "@@ -1 +1 @@".to_string(),
-            old_start: 1,
-            old_lines: 1,
-            new_start: 1,
-            new_lines: 1,
-            content_hash: "deadbeef".to_string(),
-            kind: RepoDiffKind::Unstaged,
-        }];
-
-        assign_hunks(&summary, &created.id, "src/main.rs", &hunks).expect("assign hunks");
+        let current = git::diff_hunks_for_path(&summary, "src/main.rs", RepoDiffKind::Unstaged)
+            .expect("diff hunks");
+        let mut hunk = assignment_for(&current[0]);
+        hunk.content = Some("+forged".to_string());
+        assign_hunks(&summary, &created.id, "src/main.rs", &[hunk.clone()])
+            .expect("assign hunks");
 
         let state = load_state(&summary).expect("load state");
         let entry = state.hunk_assignments.get("src/main.rs").expect("hunks");
         assert_eq!(entry.changelist_id, created.id);
         assert_eq!(entry.hunks.len(), 1);
+        assert_eq!(entry.hunks[0].content.as_deref(), Some(current[0].content.as_str()));
+
+        hunk.content_hash = "deadbeef".to_string();
+        assert!(assign_hunks(&summary, &created.id, "src/main.rs", &[hunk]).is_err());
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    /// Replaces the fake `.git` from `temp_repo` with a real repository
+    /// holding one committed file.
+    fn init_committed_repo(path: &Path, file: &str, content: &str) {
+        fs::remove_dir_all(path.join(".git")).expect("clear fake git dir");
+        let repo = git2::Repository::init(path).expect("init repo");
+        let file_path = path.join(file);
+        fs::create_dir_all(file_path.parent().expect("parent")).expect("create dirs");
+        fs::write(&file_path, content).expect("write file");
+        let mut index = repo.index().expect("index");
+        index.add_path(Path::new(file)).expect("stage file");
+        index.write().expect("write index");
+        let tree = repo
+            .find_tree(index.write_tree().expect("write tree"))
+            .expect("tree");
+        let sig = git2::Signature::now("gitpanel-test", "test@example.com").expect("signature");
+        repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+            .expect("commit");
+    }
+
+    fn diff_hunk(old_start: u32, new_start: u32, content: &str, hash: &str) -> DiffHunk {
+        DiffHunk {
+            path: "src/main.rs".to_string(),
+            kind: RepoDiffKind::Unstaged,
+            id: format!("{old_start}:3:{new_start}:3:{hash}"),
+            header: format!("@@ -{old_start},3 +{new_start},3 @@"),
+            old_start,
+            old_lines: 3,
+            new_start,
+            new_lines: 3,
+            content: content.to_string(),
+            content_hash: hash.to_string(),
+            file_header: String::new(),
+        }
+    }
+
+    fn assignment_for(hunk: &DiffHunk) -> HunkAssignment {
+        HunkAssignment {
+            id: hunk.id.clone(),
+            header: hunk.header.clone(),
+            old_start: hunk.old_start,
+            old_lines: hunk.old_lines,
+            new_start: hunk.new_start,
+            new_lines: hunk.new_lines,
+            content_hash: hunk.content_hash.clone(),
+            kind: hunk.kind.clone(),
+            line_ranges: None,
+            content: Some(hunk.content.clone()),
+        }
+    }
+
+    #[test]
+    fn reanchors_shifted_and_lightly_edited_hunks() {
+        let content = " a\n-b\n+B\n-c\n+C\n d";
+        let shifted_content = " a\n-b\n+B\n-c\n+C\n-e\n+E\n d";
+        let original = diff_hunk(10, 10, content, "aaaa");
+        let mut stored = assignment_for(&original);
+        stored.line_ranges = Some(vec![HunkLineRange { start: 3, end: 4 }]);
+
+        let current = vec![
+            diff_hunk(2, 2, " x\n-y\n+Y\n z", "bbbb"),
+            diff_hunk(40, 42, shifted_content, "cccc"),
+        ];
+        let (moved, changed) = reanchor_assignments(&[stored], &current);
+        assert!(changed);
+        assert_eq!(moved.len(), 1);
+        assert_eq!(moved[0].id, current[1].id);
+        assert_eq!(
+            moved[0].line_ranges,
+            Some(vec![HunkLineRange { start: 3, end: 4 }])
+        );
+    }
+
+    #[test]
+    fn keeps_unmatched_hunks_invalid() {
+        let original = diff_hunk(10, 10, " a\n-b\n+B\n d", "aaaa");
+        let stored = assignment_for(&original);
+        let current = vec![diff_hunk(12, 12, " q\n-r\n+R\n s", "dddd")];
+
+        let (moved, changed) = reanchor_assignments(std::slice::from_ref(&stored), &current);
+        assert!(!changed);
+        assert_eq!(moved[0].id, stored.id);
+    }
+
+    #[test]
+    fn skips_far_away_matches() {
+        let original = diff_hunk(10, 10, " a\n-b\n+B\n d", "aaaa");
+        let stored = assignment_for(&original);
+        let current = vec![diff_hunk(900, 900, " a\n-b\n+B\n-c\n+C\n d", "eeee")];
+
+        let (moved, changed) = reanchor_assignments(std::slice::from_ref(&stored), &current);
+        assert!(!changed);
+        assert_eq!(moved[0].id, stored.id);
+    }
+
+    #[test]
+    fn reanchored_whole_hunk_does_not_commit_inserted_lines() {
+        let (summary, path) = temp_repo();
+        init_committed_repo(&path, "src/main.rs", "a\nb\nc\n");
+        fs::write(path.join("src/main.rs"), "a\nB\nc\n").expect("edit file");
+
+        let created = create(&summary, "Feature").expect("create changelist");
+        let current = git::diff_hunks_for_path(&summary, "src/main.rs", RepoDiffKind::Unstaged)
+            .expect("diff hunks");
+        assign_hunks(&summary, &created.id, "src/main.rs", &[assignment_for(&current[0])])
+            .expect("assign hunks");
+
+        fs::write(path.join("src/main.rs"), "a\nB\nextra\nc\n").expect("insert line");
+        let mut state = load_state(&summary).expect("load state");
+        assert!(reanchor_hunks(&summary, &mut state).expect("reanchor"));
+        let hunks = state.hunk_assignments["src/main.rs"].hunks.clone();
+        assert!(hunks[0].line_ranges.is_some());
+
+        git::commit_changelist_with_hunks(
+            &summary,
+            &[],
+            &[("src/main.rs".to_string(), hunks)],
+            "partial",
+            &CommitOptions::default(),
+        )
+        .expect("commit reanchored hunk");
+
+        let repo = git2::Repository::open(&path).expect("open repo");
+        let tree = repo
+            .head()
+            .expect("head")
+            .peel_to_tree()
+            .expect("head tree");
+        let entry = tree.get_path(Path::new("src/main.rs")).expect("file in head");
+        let blob = repo.find_blob(entry.id()).expect("blob");
+        assert_eq!(blob.content(), b"a\nB\nc\n");
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn shelve_and_unshelve_round_trip() {
+        let (summary, path) = temp_repo();
+        fs::remove_dir_all(path.join(".git")).expect("clear fake git dir");
+        let repo = git2::Repository::init(&path).expect("init repo");
+        fs::write(path.join("tracked.txt"), "one\n").expect("write tracked");
+        fs::write(path.join("other.txt"), "other\n").expect("write other");
+        let mut index = repo.index().expect("index");
+        index
+            .add_path(std::path::Path::new("tracked.txt"))
+            .expect("stage tracked");
+        index
+            .add_path(std::path::Path::new("other.txt"))
+            .expect("stage other");
+        index.write().expect("write index");
+        let tree = repo
+            .find_tree(index.write_tree().expect("write tree"))
+            .expect("tree");
+        let sig = git2::Signature::now("gitpanel-test", "test@example.com").expect("signature");
+        repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+            .expect("commit");
+
+        fs::write(path.join("tracked.txt"), "one\ntwo\n").expect("modify tracked");
+        fs::write(path.join("new.txt"), "new\n").expect("write new");
+        fs::write(path.join("other.txt"), "other changed\n").expect("modify other");
+        let created = create(&summary, "Task").expect("create changelist");
+        assign_files(
+            &summary,
+            &created.id,
+            &["tracked.txt".to_string(), "new.txt".to_string()],
+        )
+        .expect("assign files");
+
+        let entry = shelve(&summary, &created.id).expect("shelve");
+        assert_eq!(entry.files.len(), 2);
+        assert_eq!(
+            fs::read_to_string(path.join("tracked.txt")).expect("read tracked"),
+            "one\n"
+        );
+        assert!(!path.join("new.txt").exists());
+        assert_eq!(
+            fs::read_to_string(path.join("other.txt")).expect("read other"),
+            "other changed\n"
+        );
+        assert!(load_state(&summary)
+            .expect("load state")
+            .assignments
+            .is_empty());
+
+        assert!(unshelve(&summary, "../../changelists").is_err());
+        assert!(unshelve(&summary, "shelf-1/../x").is_err());
+        let result = unshelve(&summary, &entry.id).expect("unshelve");
+        assert!(result.conflicts.is_empty());
+        assert_eq!(
+            fs::read_to_string(path.join("tracked.txt")).expect("read tracked"),
+            "one\ntwo\n"
+        );
+        assert_eq!(
+            fs::read_to_string(path.join("new.txt")).expect("read new"),
+            "new\n"
+        );
+        let state = load_state(&summary).expect("load state");
+        assert_eq!(state.assignments.get("new.txt"), Some(&created.id));
+        assert!(list_shelf(&summary).expect("list shelf").is_empty());
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn shelve_refuses_partly_staged_files() {
+        let (summary, path) = temp_repo();
+        init_committed_repo(&path, "tracked.txt", "one\n");
+        fs::write(path.join("tracked.txt"), "one\ntwo\n").expect("modify tracked");
+        let repo = git2::Repository::open(&path).expect("open repo");
+        let mut index = repo.index().expect("index");
+        index.add_path(Path::new("tracked.txt")).expect("stage tracked");
+        index.write().expect("write index");
+        fs::write(path.join("tracked.txt"), "one\ntwo\nthree\n").expect("edit tracked again");
+
+        let created = create(&summary, "Task").expect("create changelist");
+        assign_files(&summary, &created.id, &["tracked.txt".to_string()]).expect("assign file");
+
+        match shelve(&summary, &created.id) {
+            Err(RepoError::PartiallyStaged { paths, .. }) => {
+                assert_eq!(paths, vec!["tracked.txt".to_string()]);
+            }
+            other => panic!("expected partly staged refusal, got {other:?}"),
+        }
+        assert_eq!(
+            fs::read_to_string(path.join("tracked.txt")).expect("read tracked"),
+            "one\ntwo\nthree\n"
+        );
+        assert!(list_shelf(&summary).expect("list shelf").is_empty());
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn drafts_are_seeded_from_templates_and_kept_in_history() {
+        let (summary, path) = temp_repo();
+        fs::write(path.join(".gitmessage"), "# comment\nfeat: \n").expect("write template");
+        let state = load_state(&summary).expect("load state");
+        assert_eq!(state.lists[0].draft.as_deref(), Some("feat: \n"));
+
+        fs::remove_dir_all(path.join(".git")).expect("clear fake git dir");
+        let repo = git2::Repository::init(&path).expect("init repo");
+        fs::write(path.join("template.txt"), "fix: \n\nRefs: \n").expect("write template");
+        repo.config()
+            .expect("config")
+            .set_str("commit.template", "template.txt")
+            .expect("set template");
+        let created = create(&summary, "Feature").expect("create");
+        assert_eq!(created.draft.as_deref(), Some("fix: \n\nRefs: \n"));
+
+        set_draft(&summary, &created.id, "fix: typed text").expect("set draft");
+        let state = load_state(&summary).expect("load state");
+        let list = state.lists.iter().find(|item| item.id == created.id);
+        assert_eq!(
+            list.and_then(|item| item.draft.as_deref()),
+            Some("fix: typed text")
+        );
+        assert!(set_draft(&summary, "missing", "text").is_err());
+
+        record_message(&summary, &created.id, "fix: typed text\n").expect("record");
+        for index in 0..MESSAGE_HISTORY_LIMIT {
+            record_message(&summary, &created.id, &format!("message {index}")).expect("record");
+        }
+        record_message(&summary, &created.id, "message 3").expect("record");
+        let state = load_state(&summary).expect("load state");
+        assert_eq!(state.message_history.len(), MESSAGE_HISTORY_LIMIT);
+        assert_eq!(state.message_history[0], "message 3");
+        assert_eq!(state.message_history[1], "message 19");
+        assert!(!state.message_history.contains(&"fix: typed text".to_string()));
+        let list = state.lists.iter().find(|item| item.id == created.id);
+        assert_eq!(
+            list.and_then(|item| item.draft.as_deref()),
+            Some("fix: \n\nRefs: \n")
+        );
 
         let _ = fs::remove_dir_all(path);
     }
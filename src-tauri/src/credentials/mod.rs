@@ -0,0 +1,215 @@
+use std::cell::{Cell, RefCell};
+use std::path::PathBuf;
+
+use git2::{Config, Cred, CredentialType, Repository};
+
+use crate::model::{CredentialInput, CredentialKind, RepoError};
+
+const SSH_KEY_NAMES: [&str; 3] = ["id_ed25519", "id_ecdsa", "id_rsa"];
+
+/// Answers libgit2 credential requests by trying, once each: credentials
+/// supplied with the request, ssh-agent, default SSH key files and the
+/// configured `credential.helper`. When everything has been tried the
+/// challenge is recorded so the caller can report `RepoError::AuthRequired`.
+pub struct CredentialProvider {
+    supplied: Option<CredentialInput>,
+    config: Option<Config>,
+    tried_supplied_key: Cell<bool>,
+    tried_agent: Cell<bool>,
+    next_key_file: Cell<usize>,
+    tried_supplied_password: Cell<bool>,
+    tried_helper: Cell<bool>,
+    tried_default: Cell<bool>,
+    challenge: RefCell<Option<RepoError>>,
+}
+
+impl CredentialProvider {
+    pub fn new(repo: &Repository, supplied: Option<CredentialInput>) -> Self {
+        Self::with_config(repo.config().ok(), supplied)
+    }
+
+    pub fn with_config(config: Option<Config>, supplied: Option<CredentialInput>) -> Self {
+        Self {
+            supplied,
+            config,
+            tried_supplied_key: Cell::new(false),
+            tried_agent: Cell::new(false),
+            next_key_file: Cell::new(0),
+            tried_supplied_password: Cell::new(false),
+            tried_helper: Cell::new(false),
+            tried_default: Cell::new(false),
+            challenge: RefCell::new(None),
+        }
+    }
+
+    pub fn credentials(
+        &self,
+        url: &str,
+        username_from_url: Option<&str>,
+        allowed: CredentialType,
+    ) -> Result<Cred, git2::Error> {
+        // The SSH user comes from the URL; a supplied username is only used
+        // for HTTPS passwords.
+        let ssh_username = username_from_url.unwrap_or("git");
+
+        if allowed.contains(CredentialType::USERNAME) {
+            return Cred::username(ssh_username);
+        }
+
+        if allowed.contains(CredentialType::SSH_KEY) {
+            if let Some(cred) = self.next_ssh_key(ssh_username) {
+                return Ok(cred);
+            }
+        }
+
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if let Some(cred) = self.next_user_pass(url, username_from_url) {
+                return Ok(cred);
+            }
+        }
+
+        if allowed.contains(CredentialType::DEFAULT) && !self.tried_default.replace(true) {
+            return Cred::default();
+        }
+
+        let mut kinds = Vec::new();
+        if allowed.contains(CredentialType::SSH_KEY) {
+            kinds.push(CredentialKind::SshKey);
+        }
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            kinds.push(CredentialKind::UserPassword);
+        }
+        self.challenge.replace(Some(RepoError::AuthRequired {
+            message: format!("Authentication required for {url}."),
+            url: url.to_string(),
+            username: username_from_url.map(|value| value.to_string()),
+            allowed: kinds,
+        }));
+        Err(git2::Error::from_str("authentication required"))
+    }
+
+    /// Maps a failed network operation to `AuthRequired` when it ran out of
+    /// credentials, otherwise to a plain git error.
+    pub fn map_error(&self, error: git2::Error) -> RepoError {
+        if let Some(challenge) = self.challenge.borrow_mut().take() {
+            return challenge;
+        }
+        RepoError::GitError {
+            message: error.to_string(),
+        }
+    }
+
+    fn next_ssh_key(&self, username: &str) -> Option<Cred> {
+        let passphrase = self
+            .supplied
+            .as_ref()
+            .and_then(|input| input.passphrase.as_deref());
+
+        if !self.tried_supplied_key.replace(true) {
+            let key_path = self
+                .supplied
+                .as_ref()
+                .and_then(|input| input.ssh_key_path.as_deref());
+            if let Some(key_path) = key_path {
+                if let Ok(cred) =
+                    Cred::ssh_key(username, None, &PathBuf::from(key_path), passphrase)
+                {
+                    return Some(cred);
+                }
+            }
+        }
+
+        if !self.tried_agent.replace(true) {
+            if let Ok(cred) = Cred::ssh_key_from_agent(username) {
+                return Some(cred);
+            }
+        }
+
+        let ssh_dir = home_dir()?.join(".ssh");
+        while self.next_key_file.get() < SSH_KEY_NAMES.len() {
+            let name = SSH_KEY_NAMES[self.next_key_file.get()];
+            self.next_key_file.set(self.next_key_file.get() + 1);
+            let private_key = ssh_dir.join(name);
+            if !private_key.is_file() {
+                continue;
+            }
+            let public_key = ssh_dir.join(format!("{name}.pub"));
+            let public_key = public_key.is_file().then_some(public_key);
+            if let Ok(cred) =
+                Cred::ssh_key(username, public_key.as_deref(), &private_key, passphrase)
+            {
+                return Some(cred);
+            }
+        }
+        None
+    }
+
+    fn next_user_pass(&self, url: &str, username_from_url: Option<&str>) -> Option<Cred> {
+        if !self.tried_supplied_password.replace(true) {
+            if let Some(input) = self.supplied.as_ref() {
+                if let (Some(username), Some(password)) =
+                    (input.username.as_deref(), input.password.as_deref())
+                {
+                    if let Ok(cred) = Cred::userpass_plaintext(username, password) {
+                        return Some(cred);
+                    }
+                }
+            }
+        }
+
+        if !self.tried_helper.replace(true) {
+            if let Some(config) = self.config.as_ref() {
+                if let Ok(cred) = Cred::credential_helper(config, url, username_from_url) {
+                    return Some(cred);
+                }
+            }
+        }
+        None
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CredentialProvider;
+    use crate::model::{CredentialInput, CredentialKind, RepoError};
+    use git2::{Config, CredentialType};
+
+    #[test]
+    fn supplied_password_is_tried_once_then_auth_is_required() {
+        let config = Config::new().expect("empty config");
+        let provider = CredentialProvider::with_config(
+            Some(config),
+            Some(CredentialInput {
+                username: Some("octo".to_string()),
+                password: Some("secret".to_string()),
+                ssh_key_path: None,
+                passphrase: None,
+            }),
+        );
+        let url = "https://example.com/repo.git";
+
+        assert!(provider
+            .credentials(url, None, CredentialType::USER_PASS_PLAINTEXT)
+            .is_ok());
+        let retry = provider.credentials(url, None, CredentialType::USER_PASS_PLAINTEXT);
+        assert!(retry.is_err());
+
+        match provider.map_error(git2::Error::from_str("auth failed")) {
+            RepoError::AuthRequired {
+                url: failed,
+                allowed,
+                ..
+            } => {
+                assert_eq!(failed, url);
+                assert_eq!(allowed, vec![CredentialKind::UserPassword]);
+            }
+            other => panic!("expected auth required, got {other:?}"),
+        }
+    }
+}
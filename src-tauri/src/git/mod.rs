@@ -1,4 +1,6 @@
+use std::cell::RefCell;
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -8,37 +10,50 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use std::os::windows::process::CommandExt;
 
 use git2::{
-    build::CheckoutBuilder, BranchType, DiffFormat, DiffOptions, ErrorCode, FetchOptions,
-    IndexEntryExtendedFlag, ObjectType, RemoteCallbacks, Repository, Status, StatusOptions,
+    build::CheckoutBuilder, BlameOptions, BranchType, CheckoutNotificationType, Delta,
+    DiffFindOptions, DiffFormat, DiffOptions, ErrorCode, FetchOptions, IndexEntryExtendedFlag,
+    ObjectType, Oid, Patch, PushOptions, RemoteCallbacks, Repository, RepositoryState,
+    RevparseMode, Sort, StashFlags, Status, StatusOptions,
 };
 
+use crate::credentials::CredentialProvider;
 use crate::model::{
-    BranchList, CheckoutResult, CheckoutTarget, CheckoutTargetKind, CommitOptions, CommitResult,
-    DiffHunk, HunkAssignment, RepoCounts, RepoDiffKind, RepoError, RepoHead, RepoId, RepoStatus,
-    RepoSummary, StatusFile, StatusKind, UnifiedDiffText,
+    AheadBehind, BlameRange, BlameResult, BranchList, CheckoutMode, CheckoutResult, CheckoutTarget,
+    CheckoutTargetKind, CommitApplyResult, CommitDetail, CommitFileChange, CommitOptions,
+    CommitResult, CommitSignature, ConflictBlob, ConflictResolution, ConflictVersions,
+    CredentialInput, DiffFileSummary, DiffHunk, FileChangeKind, FileHistoryEntry, FileHistoryPage,
+    HunkAssignment, HunkLineRange, LogEntry, LogPage, OperationProgress, PullResult, PullStrategy,
+    PushRejectReason, PushRejection, PushResult, RangeDiff, RepoCounts, RepoDiffKind, RepoError,
+    RepoHead, RepoId, RepoLogRequest, RepoOperation, RepoStatus, RepoSummary, ResetMode,
+    ResetResult, SignatureStatus, StashDetail, StashEntry, StatusFile, StatusKind, TagInfo,
+    UnifiedDiffText,
 };
 use crate::model::{WorktreeInfo, WorktreeList, WorktreeResult};
 
+const DEFAULT_LOG_LIMIT: usize = 100;
+const MAX_LOG_LIMIT: usize = 1000;
+
 pub fn commit_changelist(
     summary: &RepoSummary,
     files: &[StatusFile],
     message: &str,
     options: &CommitOptions,
-) -> Result<CommitResult, String> {
+) -> Result<CommitResult, RepoError> {
+    let git_error = |message: String| RepoError::GitError { message };
     if files.is_empty() {
-        return Err("No files to commit.".to_string());
+        return Err(git_error("No files to commit.".to_string()));
     }
     if message.trim().is_empty() {
-        return Err("Commit message is required.".to_string());
+        return Err(git_error("Commit message is required.".to_string()));
     }
 
-    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let repo = Repository::open(&summary.path).map_err(|e| git_error(e.to_string()))?;
     let git_dir = repo.path();
     let tmp_dir = git_dir.join("gitpanel").join("tmp");
-    std::fs::create_dir_all(&tmp_dir).map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&tmp_dir).map_err(|e| git_error(e.to_string()))?;
     let millis = SystemTime::now()
         .duration_since(UNIX_EPOCH)
-        .map_err(|_| "clock error".to_string())?
+        .map_err(|_| git_error("clock error".to_string()))?
         .as_millis();
     let index_path = tmp_dir.join(format!("index-{millis}"));
 
@@ -52,62 +67,49 @@ pub fn commit_changelist(
             &summary.path,
             &["read-tree", "HEAD"],
             index_env.as_ref(),
-        )?;
+        )
+        .map_err(git_error)?;
     } else {
         run_git(
             &summary.path,
             &["read-tree", "--empty"],
             index_env.as_ref(),
-        )?;
+        )
+        .map_err(git_error)?;
     }
 
     let mut args = vec!["add", "-A", "--"];
     for file in files {
         if matches!(file.status, StatusKind::Conflicted) {
-            return Err("Changelist contains conflicted files.".to_string());
+            return Err(git_error(
+                "Changelist contains conflicted files.".to_string(),
+            ));
         }
         args.push(&file.path);
     }
-    run_git(&summary.path, &args, index_env.as_ref())?;
-
-    let tree_oid = run_git(&summary.path, &["write-tree"], index_env.as_ref())?;
-    let tree_oid = tree_oid.trim();
-
-    let commit_oid = if options.amend {
-        let head_oid = head_oid.clone().ok_or_else(|| "Cannot amend without existing commits.".to_string())?;
-        let parents_line =
-            run_git(&summary.path, &["rev-list", "--parents", "-n", "1", "HEAD"], None)?;
-        let mut parts = parents_line.split_whitespace();
-        let _ = parts.next();
-        let parents: Vec<&str> = parts.collect();
-
-        let mut commit_args = vec!["commit-tree", tree_oid, "-m", message];
-        for parent in parents {
-            commit_args.push("-p");
-            commit_args.push(parent);
-        }
-        let new_oid = run_git(&summary.path, &commit_args, None)?;
-        update_ref(&summary.path, &head_oid, new_oid.trim())?;
-        new_oid.trim().to_string()
-    } else if let Some(head_oid) = head_oid.clone() {
-        let commit_args = ["commit-tree", tree_oid, "-m", message, "-p", head_oid.as_str()];
-        let new_oid = run_git(&summary.path, &commit_args, None)?;
-        update_ref(&summary.path, &head_oid, new_oid.trim())?;
-        new_oid.trim().to_string()
-    } else {
-        let commit_args = ["commit-tree", tree_oid, "-m", message];
-        let new_oid = run_git(&summary.path, &commit_args, None)?;
-        update_ref(&summary.path, "", new_oid.trim())?;
-        new_oid.trim().to_string()
-    };
-
+    run_git(&summary.path, &args, index_env.as_ref()).map_err(git_error)?;
+
+    let committed = finish_commit(
+        &repo,
+        summary,
+        &index_path,
+        head_oid.as_deref(),
+        message,
+        options,
+    );
     let _ = std::fs::remove_file(&index_path);
+    let commit_oid = committed?;
 
-    let head = repo_head(&repo)?;
+    let head = repo_head(&repo).map_err(git_error)?;
+    let signature = commit_signature_status(summary, &commit_oid);
+    let (author, committer) = commit_identity(&repo, &commit_oid).map_err(git_error)?;
     Ok(CommitResult {
         head,
         commit_id: commit_oid,
         committed_paths: files.iter().map(|file| file.path.clone()).collect(),
+        signature,
+        author,
+        committer,
     })
 }
 
@@ -117,21 +119,22 @@ pub fn commit_changelist_with_hunks(
     hunk_files: &[(String, Vec<HunkAssignment>)],
     message: &str,
     options: &CommitOptions,
-) -> Result<CommitResult, String> {
+) -> Result<CommitResult, RepoError> {
+    let git_error = |message: String| RepoError::GitError { message };
     if full_files.is_empty() && hunk_files.is_empty() {
-        return Err("No files to commit.".to_string());
+        return Err(git_error("No files to commit.".to_string()));
     }
     if message.trim().is_empty() {
-        return Err("Commit message is required.".to_string());
+        return Err(git_error("Commit message is required.".to_string()));
     }
 
-    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let repo = Repository::open(&summary.path).map_err(|e| git_error(e.to_string()))?;
     let git_dir = repo.path();
     let tmp_dir = git_dir.join("gitpanel").join("tmp");
-    std::fs::create_dir_all(&tmp_dir).map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&tmp_dir).map_err(|e| git_error(e.to_string()))?;
     let millis = SystemTime::now()
         .duration_since(UNIX_EPOCH)
-        .map_err(|_| "clock error".to_string())?
+        .map_err(|_| git_error("clock error".to_string()))?
         .as_millis();
     let index_path = tmp_dir.join(format!("index-{millis}"));
 
@@ -140,92 +143,611 @@ pub fn commit_changelist_with_hunks(
 
     let index_env = Some(("GIT_INDEX_FILE", index_path.to_string_lossy().to_string()));
 
-    if head_oid.is_some() {
-        run_git(&summary.path, &["read-tree", "HEAD"], index_env.as_ref())?;
+    build_changelist_index(
+        summary,
+        &tmp_dir,
+        index_env.as_ref(),
+        head_oid.is_some(),
+        full_files,
+        hunk_files,
+    )
+    .map_err(git_error)?;
+
+    let committed = finish_commit(
+        &repo,
+        summary,
+        &index_path,
+        head_oid.as_deref(),
+        message,
+        options,
+    );
+    let _ = std::fs::remove_file(&index_path);
+    let commit_oid = committed?;
+
+    let head = repo_head(&repo).map_err(git_error)?;
+    let mut committed_paths: Vec<String> =
+        full_files.iter().map(|file| file.path.clone()).collect();
+    committed_paths.extend(hunk_files.iter().map(|(path, _)| path.clone()));
+
+    let signature = commit_signature_status(summary, &commit_oid);
+    let (author, committer) = commit_identity(&repo, &commit_oid).map_err(git_error)?;
+    Ok(CommitResult {
+        head,
+        commit_id: commit_oid,
+        committed_paths,
+        signature,
+        author,
+        committer,
+    })
+}
+
+/// Commits the prepared temporary index the way `git commit` would: runs
+/// `pre-commit` against it, passes the message through `prepare-commit-msg`
+/// and `commit-msg`, writes the tree and commit, then runs `post-commit`.
+/// `no_verify` skips `pre-commit` and `commit-msg`, as with git.
+fn finish_commit(
+    repo: &Repository,
+    summary: &RepoSummary,
+    index_path: &Path,
+    head_oid: Option<&str>,
+    message: &str,
+    options: &CommitOptions,
+) -> Result<String, RepoError> {
+    let git_error = |message: String| RepoError::GitError { message };
+    let hooks_dir = hooks_dir(summary).map_err(git_error)?;
+    let author_env = author_env(repo, head_oid, options).map_err(git_error)?;
+    let mut env = vec![
+        ("GIT_INDEX_FILE", index_path.to_string_lossy().to_string()),
+        ("GIT_EDITOR", ":".to_string()),
+    ];
+    env.extend(author_env.iter().cloned());
+
+    if !options.no_verify {
+        run_hook(summary, &hooks_dir, "pre-commit", &[], &env)?;
+    }
+
+    let message_path = repo.path().join("COMMIT_EDITMSG");
+    std::fs::write(&message_path, format!("{}\n", message.trim_end()))
+        .map_err(|e| git_error(e.to_string()))?;
+    let message_arg = message_path.to_string_lossy().to_string();
+    append_trailers(summary, &message_arg, options).map_err(git_error)?;
+    run_hook(
+        summary,
+        &hooks_dir,
+        "prepare-commit-msg",
+        &[&message_arg, "message"],
+        &env,
+    )?;
+    if !options.no_verify {
+        run_hook(summary, &hooks_dir, "commit-msg", &[&message_arg], &env)?;
+    }
+    let message = std::fs::read_to_string(&message_path).map_err(|e| git_error(e.to_string()))?;
+    let message = cleanup_message(&message);
+    if message.is_empty() {
+        return Err(git_error(
+            "Commit message is empty after running hooks.".to_string(),
+        ));
+    }
+
+    let index_env = ("GIT_INDEX_FILE", index_path.to_string_lossy().to_string());
+    let tree_oid = run_git(&summary.path, &["write-tree"], Some(&index_env)).map_err(git_error)?;
+    let commit_oid = write_commit(
+        repo,
+        summary,
+        tree_oid.trim(),
+        head_oid,
+        &message,
+        options,
+        &author_env,
+    )
+    .map_err(git_error)?;
+
+    // Like git, a failing post-commit hook does not undo the commit.
+    if let Err(error) = run_hook(summary, &hooks_dir, "post-commit", &[], &env) {
+        tracing::warn!(repo_id = %summary.repo_id, error = ?error, "post-commit hook failed");
+    }
+    Ok(commit_oid)
+}
+
+/// Strips trailing whitespace and surrounding blank lines, like git's
+/// `whitespace` cleanup mode.
+fn cleanup_message(message: &str) -> String {
+    let lines: Vec<&str> = message.lines().map(str::trim_end).collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+/// Resolves the hooks directory, honoring `core.hooksPath` and linked
+/// worktrees.
+fn hooks_dir(summary: &RepoSummary) -> Result<PathBuf, String> {
+    let output = run_git(&summary.path, &["rev-parse", "--git-path", "hooks"], None)?;
+    let path = PathBuf::from(output.trim());
+    Ok(if path.is_absolute() {
+        path
     } else {
-        run_git(
-            &summary.path,
-            &["read-tree", "--empty"],
-            index_env.as_ref(),
-        )?;
+        Path::new(&summary.path).join(path)
+    })
+}
+
+/// Runs `name` from `hooks_dir` if it exists and is executable. A non-zero
+/// exit is reported as `HookFailed` with the hook's combined output.
+fn run_hook(
+    summary: &RepoSummary,
+    hooks_dir: &Path,
+    name: &str,
+    args: &[&str],
+    env: &[(&str, String)],
+) -> Result<(), RepoError> {
+    let hook = hooks_dir.join(name);
+    if !is_executable(&hook) {
+        return Ok(());
+    }
+
+    // Git for Windows runs hooks through its bundled shell.
+    #[cfg(windows)]
+    let mut command = {
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        let mut command = Command::new("sh");
+        command.arg(&hook).creation_flags(CREATE_NO_WINDOW);
+        command
+    };
+    #[cfg(not(windows))]
+    let mut command = Command::new(&hook);
+    command
+        .args(args)
+        .current_dir(&summary.path)
+        .stdin(std::process::Stdio::null());
+    for (key, value) in env {
+        command.env(key, value);
+    }
+    let output = command.output().map_err(|e| RepoError::GitError {
+        message: format!("Failed to run the {name} hook: {e}"),
+    })?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let mut text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !stderr.trim().is_empty() {
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(stderr.trim());
+    }
+    Err(RepoError::HookFailed {
+        message: format!("The {name} hook rejected the commit."),
+        hook: name.to_string(),
+        exit_code: output.status.code(),
+        output: text,
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Writes the commit for `tree_oid` with `git commit-tree` and moves HEAD to
+/// it. Amending reuses the parents of the current HEAD.
+fn write_commit(
+    repo: &Repository,
+    summary: &RepoSummary,
+    tree_oid: &str,
+    head_oid: Option<&str>,
+    message: &str,
+    options: &CommitOptions,
+    author_env: &[(&str, String)],
+) -> Result<String, String> {
+    let parents_line;
+    let mut parents: Vec<&str> = Vec::new();
+    if options.amend {
+        if head_oid.is_none() {
+            return Err("Cannot amend without existing commits.".to_string());
+        }
+        parents_line =
+            run_git(&summary.path, &["rev-list", "--parents", "-n", "1", "HEAD"], None)?;
+        parents.extend(parents_line.split_whitespace().skip(1));
+    } else if let Some(head_oid) = head_oid {
+        parents.push(head_oid);
+    }
+
+    let mut commit_args = vec!["commit-tree", tree_oid, "-m", message];
+    for parent in parents {
+        commit_args.push("-p");
+        commit_args.push(parent);
+    }
+    if should_sign(repo, options)? {
+        commit_args.push("-S");
+    }
+    let new_oid = run_git_with_env(&summary.path, &commit_args, author_env)?;
+    update_ref(&summary.path, head_oid.unwrap_or(""), new_oid.trim())?;
+    Ok(new_oid.trim().to_string())
+}
+
+/// Author variables for `commit-tree` and the hooks. Amending keeps the
+/// original author unless `reset_author` is set; explicit overrides win.
+fn author_env(
+    repo: &Repository,
+    head_oid: Option<&str>,
+    options: &CommitOptions,
+) -> Result<Vec<(&'static str, String)>, String> {
+    let mut name = None;
+    let mut email = None;
+    let mut date = None;
+    if let (true, false, Some(head_oid)) = (options.amend, options.reset_author, head_oid) {
+        let oid = Oid::from_str(head_oid).map_err(|e| e.to_string())?;
+        let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+        let author = commit_signature(&commit.author());
+        let offset = author.offset_minutes;
+        let sign = if offset < 0 { '-' } else { '+' };
+        name = Some(author.name);
+        email = Some(author.email);
+        date = Some(format!(
+            "@{} {sign}{:02}{:02}",
+            author.time,
+            offset.abs() / 60,
+            offset.abs() % 60
+        ));
+    }
+    if let Some(author) = &options.author {
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        name = non_empty(&author.name).or(name);
+        email = non_empty(&author.email).or(email);
+        date = non_empty(&author.date).or(date);
+    }
+
+    let mut env = Vec::new();
+    for (key, value) in [
+        ("GIT_AUTHOR_NAME", name),
+        ("GIT_AUTHOR_EMAIL", email),
+        ("GIT_AUTHOR_DATE", date),
+    ] {
+        if let Some(value) = value {
+            env.push((key, value));
+        }
+    }
+    Ok(env)
+}
+
+/// Appends `Signed-off-by` and the requested trailers with
+/// `git interpret-trailers`, so `trailer.*` config applies as it would for
+/// `git commit --trailer`.
+fn append_trailers(
+    summary: &RepoSummary,
+    message_path: &str,
+    options: &CommitOptions,
+) -> Result<(), String> {
+    let mut trailers = Vec::new();
+    if options.signoff {
+        let ident = run_git(&summary.path, &["var", "GIT_COMMITTER_IDENT"], None)?;
+        let ident = match ident.find('>') {
+            Some(end) => &ident[..=end],
+            None => return Err("Could not determine the committer identity.".to_string()),
+        };
+        trailers.push(format!("Signed-off-by: {ident}"));
+    }
+    for trailer in &options.trailers {
+        let key = trailer.key.trim();
+        let value = trailer.value.trim();
+        if key.is_empty()
+            || value.is_empty()
+            || key.contains(|c: char| c == ':' || c.is_whitespace())
+            || value.contains('\n')
+        {
+            return Err(format!("Invalid trailer: {key}: {value}"));
+        }
+        trailers.push(format!("{key}: {value}"));
+    }
+    if trailers.is_empty() {
+        return Ok(());
+    }
+
+    let mut args = vec!["interpret-trailers", "--in-place"];
+    for trailer in &trailers {
+        args.push("--trailer");
+        args.push(trailer);
+    }
+    args.push(message_path);
+    run_git(&summary.path, &args, None)?;
+    Ok(())
+}
+
+/// Reads back the author and committer git recorded for `oid`.
+fn commit_identity(
+    repo: &Repository,
+    oid: &str,
+) -> Result<(CommitSignature, CommitSignature), String> {
+    let oid = Oid::from_str(oid).map_err(|e| e.to_string())?;
+    let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+    let author = commit_signature(&commit.author());
+    let committer = commit_signature(&commit.committer());
+    Ok((author, committer))
+}
+
+/// `commit-tree` ignores `commit.gpgsign`, so it is resolved here; the
+/// per-commit override wins. Git itself picks up `gpg.format` and
+/// `user.signingkey` once `-S` is passed.
+fn should_sign(repo: &Repository, options: &CommitOptions) -> Result<bool, String> {
+    let config = repo.config().map_err(|e| e.to_string())?;
+    let sign = options
+        .sign
+        .unwrap_or_else(|| config.get_bool("commit.gpgsign").unwrap_or(false));
+    if !sign {
+        return Ok(false);
+    }
+    let format = config
+        .get_string("gpg.format")
+        .unwrap_or_else(|_| "openpgp".to_string());
+    if format == "ssh" && config.get_string("user.signingkey").is_err() {
+        return Err("SSH commit signing requires user.signingkey.".to_string());
+    }
+    Ok(true)
+}
+
+/// Reads `%G?` for the commit. Only a good signature from a trusted key
+/// (`G`) is verified; good signatures of unknown validity (`U`) and those git
+/// cannot check, for example SSH signatures without
+/// `gpg.ssh.allowedSignersFile`, count as unverified.
+fn commit_signature_status(summary: &RepoSummary, oid: &str) -> SignatureStatus {
+    let args = ["show", "-s", "--format=%G?", oid];
+    match run_git(&summary.path, &args, None).as_deref().map(str::trim) {
+        Ok("N") => SignatureStatus::Unsigned,
+        Ok("G") => SignatureStatus::Verified,
+        _ => SignatureStatus::Unverified,
+    }
+}
+
+fn build_changelist_index(
+    summary: &RepoSummary,
+    tmp_dir: &Path,
+    index_env: Option<&(&str, String)>,
+    has_head: bool,
+    full_files: &[StatusFile],
+    hunk_files: &[(String, Vec<HunkAssignment>)],
+) -> Result<(), String> {
+    if has_head {
+        run_git(&summary.path, &["read-tree", "HEAD"], index_env)?;
+    } else {
+        run_git(&summary.path, &["read-tree", "--empty"], index_env)?;
     }
 
     if !full_files.is_empty() {
+        let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+        let head_tree = repo.head().and_then(|head| head.peel_to_tree()).ok();
         let mut args = vec!["add", "-A", "--"];
         for file in full_files {
             if matches!(file.status, StatusKind::Conflicted) {
                 return Err("Changelist contains conflicted files.".to_string());
             }
             args.push(&file.path);
+            // Include the source of a rename so its removal is captured too.
+            if let (Some(old_path), Some(tree)) = (file.old_path.as_deref(), head_tree.as_ref()) {
+                if old_path != file.path && tree.get_path(Path::new(old_path)).is_ok() {
+                    args.push(old_path);
+                }
+            }
         }
-        run_git(&summary.path, &args, index_env.as_ref())?;
+        run_git(&summary.path, &args, index_env)?;
     }
 
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| "clock error".to_string())?
+        .as_millis();
     for (path, hunks) in hunk_files {
-        let patch = build_hunk_patch(summary, path, hunks)?;
+        let patch = build_hunk_patch(summary, path, hunks, false)?;
         let patch_path = tmp_dir.join(format!("patch-{millis}-{}.diff", sanitize_path(path)));
         std::fs::write(&patch_path, patch).map_err(|e| e.to_string())?;
         let patch_path_str = patch_path.to_string_lossy().to_string();
         let args = ["apply", "--cached", patch_path_str.as_str()];
-        run_git(&summary.path, &args, index_env.as_ref())?;
+        let applied = run_git(&summary.path, &args, index_env);
         let _ = std::fs::remove_file(&patch_path);
+        applied?;
     }
+    Ok(())
+}
 
-    let tree_oid = run_git(&summary.path, &["write-tree"], index_env.as_ref())?;
-    let tree_oid = tree_oid.trim();
+/// Renders the changes of a changelist as a binary-safe patch against HEAD.
+pub fn changelist_patch(
+    summary: &RepoSummary,
+    full_files: &[StatusFile],
+    hunk_files: &[(String, Vec<HunkAssignment>)],
+) -> Result<String, String> {
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let tmp_dir = repo.path().join("gitpanel").join("tmp");
+    std::fs::create_dir_all(&tmp_dir).map_err(|e| e.to_string())?;
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| "clock error".to_string())?
+        .as_millis();
+    let index_path = tmp_dir.join(format!("index-patch-{millis}"));
+    let index_env = Some(("GIT_INDEX_FILE", index_path.to_string_lossy().to_string()));
+    let has_head = run_git(&summary.path, &["rev-parse", "--verify", "HEAD"], None).is_ok();
+
+    let built = build_changelist_index(
+        summary,
+        &tmp_dir,
+        index_env.as_ref(),
+        has_head,
+        full_files,
+        hunk_files,
+    );
+    let patch = built.and_then(|_| {
+        let mut args = vec!["diff", "--cached", "--binary", "--no-color", "--no-ext-diff"];
+        if has_head {
+            args.push("HEAD");
+        }
+        run_git(&summary.path, &args, index_env.as_ref())
+    });
+    let _ = std::fs::remove_file(&index_path);
+    patch
+}
 
-    let commit_oid = if options.amend {
-        let head_oid = head_oid
-            .clone()
-            .ok_or_else(|| "Cannot amend without existing commits.".to_string())?;
-        let parents_line =
-            run_git(&summary.path, &["rev-list", "--parents", "-n", "1", "HEAD"], None)?;
-        let mut parts = parents_line.split_whitespace();
-        let _ = parts.next();
-        let parents: Vec<&str> = parts.collect();
-
-        let mut commit_args = vec!["commit-tree", tree_oid, "-m", message];
-        for parent in parents {
-            commit_args.push("-p");
-            commit_args.push(parent);
-        }
-        let new_oid = run_git(&summary.path, &commit_args, None)?;
-        update_ref(&summary.path, &head_oid, new_oid.trim())?;
-        new_oid.trim().to_string()
-    } else if let Some(head_oid) = head_oid.clone() {
-        let commit_args = ["commit-tree", tree_oid, "-m", message, "-p", head_oid.as_str()];
-        let new_oid = run_git(&summary.path, &commit_args, None)?;
-        update_ref(&summary.path, &head_oid, new_oid.trim())?;
-        new_oid.trim().to_string()
-    } else {
-        let commit_args = ["commit-tree", tree_oid, "-m", message];
-        let new_oid = run_git(&summary.path, &commit_args, None)?;
-        update_ref(&summary.path, "", new_oid.trim())?;
-        new_oid.trim().to_string()
-    };
+/// Drops the changes of a changelist from the index and working tree.
+pub fn revert_changelist_changes(
+    summary: &RepoSummary,
+    full_files: &[StatusFile],
+    hunk_files: &[(String, Vec<HunkAssignment>)],
+) -> Result<(), String> {
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let tmp_dir = repo.path().join("gitpanel").join("tmp");
+    std::fs::create_dir_all(&tmp_dir).map_err(|e| e.to_string())?;
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| "clock error".to_string())?
+        .as_millis();
 
-    let _ = std::fs::remove_file(&index_path);
+    // Every reverse patch is checked before any is applied, so a patch that
+    // no longer matches leaves the working tree untouched.
+    let mut patches = Vec::new();
+    for (path, hunks) in hunk_files {
+        let patch = build_hunk_patch(summary, path, hunks, true)?;
+        let patch_path = tmp_dir.join(format!("revert-{millis}-{}.diff", sanitize_path(path)));
+        std::fs::write(&patch_path, patch).map_err(|e| e.to_string())?;
+        let staged = hunks.iter().any(|hunk| hunk.kind == RepoDiffKind::Staged);
+        patches.push((patch_path, staged));
+    }
+    let reverted = revert_hunk_patches(summary, &patches);
+    for (patch_path, _) in &patches {
+        let _ = std::fs::remove_file(patch_path);
+    }
+    reverted?;
+
+    let head_tree = repo.head().and_then(|head| head.peel_to_tree()).ok();
+    let mut in_head: Vec<&str> = Vec::new();
+    let mut not_in_head: Vec<&str> = Vec::new();
+    for file in full_files {
+        for path in std::iter::once(file.path.as_str()).chain(file.old_path.as_deref()) {
+            let tracked = head_tree
+                .as_ref()
+                .map(|tree| tree.get_path(Path::new(path)).is_ok())
+                .unwrap_or(false);
+            if tracked {
+                in_head.push(path);
+            } else {
+                not_in_head.push(path);
+            }
+        }
+    }
 
-    let head = repo_head(&repo)?;
-    let mut committed_paths: Vec<String> =
-        full_files.iter().map(|file| file.path.clone()).collect();
-    committed_paths.extend(hunk_files.iter().map(|(path, _)| path.clone()));
+    if !in_head.is_empty() {
+        let mut args = vec!["checkout", "HEAD", "--"];
+        args.extend(in_head);
+        run_git(&summary.path, &args, None)?;
+    }
+    if !not_in_head.is_empty() {
+        let mut args = vec!["rm", "-q", "--cached", "--ignore-unmatch", "--"];
+        args.extend(not_in_head.iter().copied());
+        run_git(&summary.path, &args, None)?;
+        let root = Path::new(&summary.worktree_path);
+        for path in not_in_head {
+            let target = root.join(path);
+            if target.is_file() {
+                std::fs::remove_file(&target).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    Ok(())
+}
 
-    Ok(CommitResult {
-        head,
-        commit_id: commit_oid,
-        committed_paths,
-    })
+fn revert_hunk_patches(summary: &RepoSummary, patches: &[(PathBuf, bool)]) -> Result<(), String> {
+    for check in [true, false] {
+        for (patch_path, staged) in patches {
+            let patch_path = patch_path.to_string_lossy().to_string();
+            let mut targets = Vec::new();
+            if *staged {
+                targets.push(Some("--cached"));
+            }
+            targets.push(None);
+            for target in targets {
+                let mut args = vec!["apply", "-R"];
+                args.extend(check.then_some("--check"));
+                args.extend(target);
+                args.push(patch_path.as_str());
+                run_git(&summary.path, &args, None)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Applies a stored patch to the working tree, falling back to a three-way
+/// merge when it no longer applies cleanly. Returns the conflicted paths.
+pub fn apply_patch_file(summary: &RepoSummary, patch_path: &Path) -> Result<Vec<String>, String> {
+    let patch_path_str = patch_path.to_string_lossy().to_string();
+    if run_git(&summary.path, &["apply", patch_path_str.as_str()], None).is_ok() {
+        return Ok(Vec::new());
+    }
+
+    let three_way = run_git(&summary.path, &["apply", "--3way", patch_path_str.as_str()], None);
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let conflicts = conflicted_paths(&repo)?;
+    if let Err(error) = three_way {
+        if conflicts.is_empty() {
+            return Err(error);
+        }
+    }
+
+    // --3way goes through the index; put clean paths back to unstaged.
+    let patch = std::fs::read_to_string(patch_path).map_err(|e| e.to_string())?;
+    let clean: Vec<String> = patch
+        .lines()
+        .filter(|line| line.starts_with("diff --git "))
+        .filter_map(extract_b_path)
+        .filter(|path| !conflicts.contains(path))
+        .collect();
+    if !clean.is_empty() {
+        let mut args = vec!["reset", "-q", "--"];
+        args.extend(clean.iter().map(String::as_str));
+        let _ = run_git(&summary.path, &args, None);
+    }
+    Ok(conflicts)
+}
+
+fn conflicted_paths(repo: &Repository) -> Result<Vec<String>, String> {
+    let index = repo.index().map_err(|e| e.to_string())?;
+    let mut paths = Vec::new();
+    if index.has_conflicts() {
+        for conflict in index.conflicts().map_err(|e| e.to_string())?.flatten() {
+            let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+            if let Some(entry) = entry {
+                paths.push(String::from_utf8_lossy(&entry.path).to_string());
+            }
+        }
+    }
+    Ok(paths)
 }
 
 fn run_git(
     repo_path: &str,
     args: &[&str],
     env: Option<&(&str, String)>,
+) -> Result<String, String> {
+    run_git_with_env(repo_path, args, env.map(std::slice::from_ref).unwrap_or_default())
+}
+
+fn run_git_with_env(
+    repo_path: &str,
+    args: &[&str],
+    env: &[(&str, String)],
 ) -> Result<String, String> {
     let mut command = Command::new("git");
     command.arg("-C").arg(repo_path).args(args);
-    if let Some((key, value)) = env {
+    for (key, value) in env {
         command.env(key, value);
     }
     #[cfg(windows)]
@@ -247,10 +769,15 @@ fn run_git(
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
+/// Builds a patch containing only the selected hunks and lines. A forward
+/// patch applies to the old side of the diff; with `reverse` the unselected
+/// lines are kept as they are on the new side, so the patch can be applied
+/// with `git apply -R` to the current content.
 fn build_hunk_patch(
     summary: &RepoSummary,
     path: &str,
     hunks: &[HunkAssignment],
+    reverse: bool,
 ) -> Result<String, String> {
     if hunks.is_empty() {
         return Err("no hunks provided".to_string());
@@ -267,8 +794,7 @@ fn build_hunk_patch(
         lookup.insert(hunk.id.clone(), hunk);
     }
 
-    let mut file_header = String::new();
-    let mut patch = String::new();
+    let mut selected = Vec::new();
     for hunk in hunks {
         let diff = lookup
             .get(&hunk.id)
@@ -276,6 +802,20 @@ fn build_hunk_patch(
         if diff.content_hash != hunk.content_hash {
             return Err("hunk content changed; reselect required".to_string());
         }
+        selected.push((diff, hunk.line_ranges.as_deref()));
+    }
+    selected.sort_by_key(|(diff, _)| diff.old_start);
+
+    let mut file_header = String::new();
+    let mut patch = String::new();
+    let mut offset: i64 = 0;
+    for (diff, ranges) in selected {
+        if ranges.is_some() && diff.file_header.contains("deleted file mode") {
+            return Err("line selection is not supported for deleted files".to_string());
+        }
+        let Some(partial) = select_hunk_lines(&diff.content, ranges, reverse) else {
+            continue;
+        };
         if file_header.is_empty() {
             file_header = diff.file_header.clone();
             if !file_header.ends_with('\n') {
@@ -283,17 +823,132 @@ fn build_hunk_patch(
             }
             patch.push_str(&file_header);
         }
-        patch.push_str(&diff.header);
-        patch.push('\n');
-        patch.push_str(&diff.content);
-        if !diff.content.ends_with('\n') {
-            patch.push('\n');
-        }
+
+        let old_lines = partial.old_lines;
+        let new_lines = partial.new_lines;
+        let (old_start, new_start) = if reverse {
+            let new_start = i64::from(diff.new_start);
+            let mut old_start = new_start - offset;
+            if new_lines == 0 {
+                old_start += 1;
+            }
+            if old_lines == 0 {
+                old_start -= 1;
+            }
+            (old_start, new_start)
+        } else {
+            let old_start = i64::from(diff.old_start);
+            let mut new_start = old_start + offset;
+            if old_lines == 0 {
+                new_start += 1;
+            }
+            if new_lines == 0 {
+                new_start -= 1;
+            }
+            (old_start, new_start)
+        };
+        offset += i64::from(new_lines) - i64::from(old_lines);
+
+        patch.push_str(&format!(
+            "@@ -{},{} +{},{} @@{}\n",
+            old_start.max(0),
+            old_lines,
+            new_start.max(0),
+            new_lines,
+            hunk_section(&diff.header)
+        ));
+        patch.push_str(&partial.content);
     }
 
+    if patch.is_empty() {
+        return Err("no lines selected".to_string());
+    }
     Ok(patch)
 }
 
+struct PartialHunk {
+    old_lines: u32,
+    new_lines: u32,
+    content: String,
+}
+
+/// Rebuilds a hunk body keeping only the selected `+`/`-` lines. Unselected
+/// lines from the side the patch is anchored on become context: `-` lines
+/// for forward patches, `+` lines for `reverse` ones; the others are
+/// dropped. Returns `None` when no change survives the selection.
+fn select_hunk_lines(
+    content: &str,
+    ranges: Option<&[HunkLineRange]>,
+    reverse: bool,
+) -> Option<PartialHunk> {
+    let is_selected = |index: usize| match ranges {
+        None => true,
+        Some(ranges) => ranges
+            .iter()
+            .any(|range| range.start as usize <= index && index <= range.end as usize),
+    };
+
+    let mut old_lines = 0;
+    let mut new_lines = 0;
+    let mut has_change = false;
+    let mut out = String::new();
+    let mut previous_kept = false;
+    for (index, line) in content.lines().enumerate() {
+        let (marker, text) = match line.chars().next() {
+            Some(marker) => (marker, &line[marker.len_utf8()..]),
+            None => (' ', ""),
+        };
+        let kept_line = match marker {
+            '+' if is_selected(index) => {
+                new_lines += 1;
+                has_change = true;
+                Some(format!("+{text}"))
+            }
+            '-' if is_selected(index) => {
+                old_lines += 1;
+                has_change = true;
+                Some(format!("-{text}"))
+            }
+            '+' if !reverse => None,
+            '-' if reverse => None,
+            '+' | '-' | ' ' => {
+                old_lines += 1;
+                new_lines += 1;
+                Some(format!(" {text}"))
+            }
+            '\\' => {
+                if previous_kept {
+                    out.push_str(line);
+                    out.push('\n');
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        previous_kept = kept_line.is_some();
+        if let Some(kept_line) = kept_line {
+            out.push_str(&kept_line);
+            out.push('\n');
+        }
+    }
+
+    if !has_change {
+        return None;
+    }
+    Some(PartialHunk {
+        old_lines,
+        new_lines,
+        content: out,
+    })
+}
+
+fn hunk_section(header: &str) -> &str {
+    header
+        .strip_prefix("@@ ")
+        .and_then(|rest| rest.find("@@").map(|end| &rest[end + 2..]))
+        .unwrap_or("")
+}
+
 fn sanitize_path(path: &str) -> String {
     path.chars()
         .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
@@ -366,6 +1021,33 @@ pub fn resolve_git_dir(worktree_path: &str) -> PathBuf {
     dot_git
 }
 
+/// Text for a new commit message draft: the file named by `commit.template`,
+/// or `.gitmessage` at the worktree root. Comment lines are dropped since
+/// panel commits do not strip them.
+pub fn commit_template(summary: &RepoSummary) -> Option<String> {
+    let worktree = Path::new(&summary.worktree_path);
+    let configured = Repository::open(worktree)
+        .and_then(|repo| repo.config())
+        .and_then(|config| config.get_path("commit.template"))
+        .ok()
+        .map(|path| worktree.join(path));
+    let content = configured
+        .into_iter()
+        .chain(std::iter::once(worktree.join(".gitmessage")))
+        .find_map(|path| std::fs::read_to_string(path).ok())?;
+    let template = content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let template = template.trim_end_matches('\n');
+    if template.trim().is_empty() {
+        None
+    } else {
+        Some(format!("{template}\n"))
+    }
+}
+
 pub fn status(summary: &RepoSummary) -> Result<RepoStatus, String> {
     let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
     let head = repo_head(&repo)?;
@@ -452,12 +1134,263 @@ pub fn status(summary: &RepoSummary) -> Result<RepoStatus, String> {
         head,
         counts,
         files,
+        operation: repo_operation(&repo),
+        rebase_progress: rebase_progress(&repo),
     })
 }
 
-pub fn diff_for_path(
-    summary: &RepoSummary,
-    path: &str,
+fn repo_operation(repo: &Repository) -> RepoOperation {
+    match repo.state() {
+        RepositoryState::Clean => RepoOperation::None,
+        RepositoryState::Merge => RepoOperation::Merge,
+        RepositoryState::Revert => RepoOperation::Revert,
+        RepositoryState::RevertSequence => RepoOperation::RevertSequence,
+        RepositoryState::CherryPick => RepoOperation::CherryPick,
+        RepositoryState::CherryPickSequence => RepoOperation::CherryPickSequence,
+        RepositoryState::Bisect => RepoOperation::Bisect,
+        RepositoryState::Rebase => RepoOperation::Rebase,
+        RepositoryState::RebaseInteractive => RepoOperation::RebaseInteractive,
+        RepositoryState::RebaseMerge => RepoOperation::RebaseMerge,
+        RepositoryState::ApplyMailbox => RepoOperation::ApplyMailbox,
+        RepositoryState::ApplyMailboxOrRebase => RepoOperation::ApplyMailboxOrRebase,
+    }
+}
+
+/// Reads the step counters git keeps in `rebase-merge` (or `rebase-apply`
+/// for am-based rebases).
+fn rebase_progress(repo: &Repository) -> Option<OperationProgress> {
+    let read = |dir: &str, file: &str| -> Option<u32> {
+        std::fs::read_to_string(repo.path().join(dir).join(file))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    let (current, total) = match (read("rebase-merge", "msgnum"), read("rebase-merge", "end")) {
+        (Some(current), Some(total)) => (current, total),
+        _ => (read("rebase-apply", "next")?, read("rebase-apply", "last")?),
+    };
+    Some(OperationProgress { current, total })
+}
+
+pub fn operation_continue(summary: &RepoSummary) -> Result<(), RepoError> {
+    let operation = current_operation(summary)?;
+    let args: &[&str] = match operation {
+        RepoOperation::Merge => &["commit", "--no-edit"],
+        RepoOperation::Rebase
+        | RepoOperation::RebaseInteractive
+        | RepoOperation::RebaseMerge
+        | RepoOperation::ApplyMailboxOrRebase => &["rebase", "--continue"],
+        RepoOperation::CherryPick | RepoOperation::CherryPickSequence => {
+            &["cherry-pick", "--continue"]
+        }
+        RepoOperation::Revert | RepoOperation::RevertSequence => &["revert", "--continue"],
+        RepoOperation::ApplyMailbox => &["am", "--continue"],
+        RepoOperation::Bisect | RepoOperation::None => {
+            return Err(RepoError::GitError {
+                message: "No operation to continue.".to_string(),
+            })
+        }
+    };
+    let conflicts = conflicted_paths_for(summary)?;
+    if !conflicts.is_empty() {
+        return Err(RepoError::MergeConflict {
+            message: "Resolve all conflicts before continuing.".to_string(),
+            paths: conflicts,
+        });
+    }
+    run_operation_step(summary, args)
+}
+
+pub fn operation_abort(summary: &RepoSummary) -> Result<(), RepoError> {
+    let args: &[&str] = match current_operation(summary)? {
+        RepoOperation::Merge => &["merge", "--abort"],
+        RepoOperation::Rebase
+        | RepoOperation::RebaseInteractive
+        | RepoOperation::RebaseMerge
+        | RepoOperation::ApplyMailboxOrRebase => &["rebase", "--abort"],
+        RepoOperation::CherryPick | RepoOperation::CherryPickSequence => {
+            &["cherry-pick", "--abort"]
+        }
+        RepoOperation::Revert | RepoOperation::RevertSequence => &["revert", "--abort"],
+        RepoOperation::ApplyMailbox => &["am", "--abort"],
+        RepoOperation::Bisect => &["bisect", "reset"],
+        RepoOperation::None => {
+            return Err(RepoError::GitError {
+                message: "No operation to abort.".to_string(),
+            })
+        }
+    };
+    run_operation_step(summary, args)
+}
+
+pub fn operation_skip(summary: &RepoSummary) -> Result<(), RepoError> {
+    let args: &[&str] = match current_operation(summary)? {
+        RepoOperation::Rebase
+        | RepoOperation::RebaseInteractive
+        | RepoOperation::RebaseMerge
+        | RepoOperation::ApplyMailboxOrRebase => &["rebase", "--skip"],
+        RepoOperation::CherryPick | RepoOperation::CherryPickSequence => {
+            &["cherry-pick", "--skip"]
+        }
+        RepoOperation::Revert | RepoOperation::RevertSequence => &["revert", "--skip"],
+        RepoOperation::ApplyMailbox => &["am", "--skip"],
+        RepoOperation::Merge | RepoOperation::Bisect | RepoOperation::None => {
+            return Err(RepoError::GitError {
+                message: "The current operation cannot skip a step.".to_string(),
+            })
+        }
+    };
+    run_operation_step(summary, args)
+}
+
+fn current_operation(summary: &RepoSummary) -> Result<RepoOperation, RepoError> {
+    let repo = Repository::open(&summary.path).map_err(|e| RepoError::GitError {
+        message: e.to_string(),
+    })?;
+    Ok(repo_operation(&repo))
+}
+
+fn conflicted_paths_for(summary: &RepoSummary) -> Result<Vec<String>, RepoError> {
+    Repository::open(&summary.path)
+        .map_err(|e| e.to_string())
+        .and_then(|repo| conflicted_paths(&repo))
+        .map_err(|message| RepoError::GitError { message })
+}
+
+/// Runs a sequencer command without an editor. A step that stops on new
+/// conflicts is reported as `MergeConflict`.
+fn run_operation_step(summary: &RepoSummary, args: &[&str]) -> Result<(), RepoError> {
+    let editor = ("GIT_EDITOR", "true".to_string());
+    let Err(message) = run_git(&summary.path, args, Some(&editor)) else {
+        return Ok(());
+    };
+    let conflicts = conflicted_paths_for(summary)?;
+    if conflicts.is_empty() {
+        return Err(RepoError::GitError { message });
+    }
+    Err(RepoError::MergeConflict {
+        message: "The next step produced conflicts.".to_string(),
+        paths: conflicts,
+    })
+}
+
+/// Reverts `revision` on top of HEAD; see `apply_commit`.
+pub fn revert(
+    summary: &RepoSummary,
+    revision: &str,
+    no_commit: bool,
+) -> Result<CommitApplyResult, RepoError> {
+    apply_commit(summary, &["revert", "--no-edit"], revision, no_commit)
+}
+
+/// Cherry-picks `revision` onto HEAD; see `apply_commit`.
+pub fn cherry_pick(
+    summary: &RepoSummary,
+    revision: &str,
+    no_commit: bool,
+) -> Result<CommitApplyResult, RepoError> {
+    apply_commit(summary, &["cherry-pick"], revision, no_commit)
+}
+
+/// Runs `revert`/`cherry-pick` for a single commit. With `no_commit` the
+/// result stays staged in the working tree. Conflicts are reported as
+/// `MergeConflict` with the operation left in progress for
+/// continue/abort. `paths` are the files the commit touched.
+fn apply_commit(
+    summary: &RepoSummary,
+    command: &[&str],
+    revision: &str,
+    no_commit: bool,
+) -> Result<CommitApplyResult, RepoError> {
+    let git_error = |message: String| RepoError::GitError { message };
+    let repo = Repository::open(&summary.path).map_err(|e| git_error(e.to_string()))?;
+    let commit = find_commit(&repo, revision).map_err(git_error)?;
+    let parent = commit
+        .parent_id(0)
+        .map_err(|_| git_error("Cannot apply a root commit.".to_string()))?;
+    let paths = changed_paths_between(&repo, parent, commit.id()).map_err(git_error)?;
+    let old_head = repo.refname_to_id("HEAD").ok();
+
+    let oid = commit.id().to_string();
+    let mut args = command.to_vec();
+    if no_commit {
+        args.push("--no-commit");
+    }
+    args.push(&oid);
+    run_operation_step(summary, &args)?;
+
+    let new_head = repo.refname_to_id("HEAD").ok();
+    let head = repo_head(&repo).map_err(git_error)?;
+    Ok(CommitApplyResult {
+        head,
+        commit_id: new_head
+            .filter(|oid| Some(*oid) != old_head)
+            .map(|oid| oid.to_string()),
+        paths,
+    })
+}
+
+/// Moves HEAD to `revision`. A hard reset only runs when `expected_head`
+/// is the current HEAD oid; otherwise it fails with `ConfirmationRequired`
+/// carrying that oid, which callers swap for a one-time nonce before it
+/// reaches the client. Before a hard reset HEAD and any uncommitted tracked
+/// changes are saved under `refs/gitpanel/backups/`.
+pub fn reset(
+    summary: &RepoSummary,
+    revision: &str,
+    mode: ResetMode,
+    expected_head: Option<&str>,
+) -> Result<ResetResult, RepoError> {
+    let git_error = |message: String| RepoError::GitError { message };
+    let repo = Repository::open(&summary.path).map_err(|e| git_error(e.to_string()))?;
+    let target = repo
+        .revparse_single(revision)
+        .and_then(|obj| obj.peel(ObjectType::Commit))
+        .map_err(|e| git_error(e.to_string()))?;
+
+    let mut backup_ref = None;
+    if mode == ResetMode::Hard {
+        let head = repo
+            .refname_to_id("HEAD")
+            .map_err(|e| git_error(e.to_string()))?
+            .to_string();
+        if expected_head != Some(head.as_str()) {
+            return Err(RepoError::ConfirmationRequired {
+                message: format!(
+                    "Hard reset to {revision} discards uncommitted changes. Confirm to continue."
+                ),
+                token: head,
+            });
+        }
+        // `stash create` records tracked changes without touching the tree;
+        // it prints nothing when there is nothing to save.
+        let stash = run_git(&summary.path, &["stash", "create"], None).map_err(git_error)?;
+        let backup = match stash.trim() {
+            "" => head,
+            oid => oid.to_string(),
+        };
+        let name = format!("refs/gitpanel/backups/reset-{}", crate::store::now_ms());
+        let oid = Oid::from_str(&backup).map_err(|e| git_error(e.to_string()))?;
+        repo.reference(&name, oid, false, "gitpanel: backup before hard reset")
+            .map_err(|e| git_error(e.to_string()))?;
+        backup_ref = Some(name);
+    }
+
+    let kind = match mode {
+        ResetMode::Soft => git2::ResetType::Soft,
+        ResetMode::Mixed => git2::ResetType::Mixed,
+        ResetMode::Hard => git2::ResetType::Hard,
+    };
+    repo.reset(&target, kind, None)
+        .map_err(|e| git_error(e.to_string()))?;
+    let head = repo_head(&repo).map_err(git_error)?;
+    Ok(ResetResult { head, backup_ref })
+}
+
+pub fn diff_for_path(
+    summary: &RepoSummary,
+    path: &str,
     kind: RepoDiffKind,
 ) -> Result<UnifiedDiffText, String> {
     let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
@@ -483,43 +1416,352 @@ pub fn diff_for_path(
             repo.diff_tree_to_index(tree.as_ref(), Some(&index), Some(&mut options))
                 .map_err(|e| e.to_string())?
         }
+        RepoDiffKind::Commit { .. } | RepoDiffKind::Range { .. } => {
+            let (old_oid, new_oid) = tree_diff_oids(summary, &kind)?
+                .ok_or_else(|| "not a tree diff".to_string())?;
+            let mut files = tree_diff_texts(summary, old_oid.as_deref(), &new_oid)?;
+            let text = files.remove(&normalized_path).unwrap_or_default();
+            return Ok(UnifiedDiffText { text });
+        }
     };
 
+    let text = diff_patch_text(&diff)?;
+    Ok(UnifiedDiffText { text })
+}
+
+fn diff_patch_text(diff: &git2::Diff) -> Result<String, String> {
     let mut text = String::new();
     diff.print(DiffFormat::Patch, |_, _, line| {
-        if let Ok(chunk) = std::str::from_utf8(line.content()) {
-            let origin = line.origin();
-            match origin {
-                // Normalize unified hunk line prefixes so downstream parsers
-                // always receive standard patch rows (+/-/ context).
-                '+' | '-' | ' ' | '\\' => {
-                    if !chunk.starts_with(origin) {
-                        text.push(origin);
-                    }
-                    text.push_str(chunk);
-                }
-                // libgit2 uses '\n' for some empty context rows; map them to
-                // a valid unified-diff context line.
-                '\n' => {
-                    if chunk == "\n" || chunk.is_empty() {
-                        text.push_str(" \n");
-                    } else {
-                        if !chunk.starts_with(' ') {
-                            text.push(' ');
-                        }
-                        text.push_str(chunk);
-                    }
-                }
-                _ => {
-                    text.push_str(chunk);
+        push_patch_line(&mut text, &line);
+        true
+    })
+    .map_err(|e| e.to_string())?;
+    Ok(text)
+}
+
+fn push_patch_line(text: &mut String, line: &git2::DiffLine) {
+    let Ok(chunk) = std::str::from_utf8(line.content()) else {
+        return;
+    };
+    let origin = line.origin();
+    match origin {
+        // Normalize unified hunk line prefixes so downstream parsers
+        // always receive standard patch rows (+/-/ context).
+        '+' | '-' | ' ' | '\\' => {
+            if !chunk.starts_with(origin) {
+                text.push(origin);
+            }
+            text.push_str(chunk);
+        }
+        // libgit2 uses '\n' for some empty context rows; map them to
+        // a valid unified-diff context line.
+        '\n' => {
+            if chunk == "\n" || chunk.is_empty() {
+                text.push_str(" \n");
+            } else {
+                if !chunk.starts_with(' ') {
+                    text.push(' ');
                 }
+                text.push_str(chunk);
             }
         }
-        true
+        _ => {
+            text.push_str(chunk);
+        }
+    }
+}
+
+fn find_renames(diff: &mut git2::Diff) -> Result<(), String> {
+    let mut find = DiffFindOptions::new();
+    find.renames(true).copies(true);
+    diff.find_similar(Some(&mut find)).map_err(|e| e.to_string())
+}
+
+/// Resolves the commits a `Commit` or `Range` diff compares: the first parent
+/// (if any) and the commit, or both ends of the range. `None` for index and
+/// working tree diffs.
+pub fn tree_diff_oids(
+    summary: &RepoSummary,
+    kind: &RepoDiffKind,
+) -> Result<Option<(Option<String>, String)>, String> {
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    match kind {
+        RepoDiffKind::Commit { oid } => {
+            let commit = find_commit(&repo, oid)?;
+            let parent = commit.parent_id(0).ok().map(|oid| oid.to_string());
+            Ok(Some((parent, commit.id().to_string())))
+        }
+        RepoDiffKind::Range { from, to } => {
+            let from_oid = find_commit(&repo, from)?.id().to_string();
+            let to_oid = find_commit(&repo, to)?.id().to_string();
+            Ok(Some((Some(from_oid), to_oid)))
+        }
+        RepoDiffKind::Staged | RepoDiffKind::Unstaged => Ok(None),
+    }
+}
+
+/// Renders the patch of every file changed between two commits, keyed by
+/// path. The whole tree is diffed at once so renames pair up, which makes
+/// the result worth caching per oid pair rather than per file.
+pub fn tree_diff_texts(
+    summary: &RepoSummary,
+    old_oid: Option<&str>,
+    new_oid: &str,
+) -> Result<HashMap<String, String>, String> {
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let old_tree = match old_oid {
+        Some(oid) => Some(find_commit(&repo, oid)?.tree().map_err(|e| e.to_string())?),
+        None => None,
+    };
+    let new_tree = find_commit(&repo, new_oid)?
+        .tree()
+        .map_err(|e| e.to_string())?;
+    let mut diff = repo
+        .diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None)
+        .map_err(|e| e.to_string())?;
+    find_renames(&mut diff)?;
+
+    let mut files = HashMap::new();
+    for (index, delta) in diff.deltas().enumerate() {
+        let Some(path) = delta.new_file().path() else {
+            continue;
+        };
+        let Some(mut patch) = Patch::from_diff(&diff, index).map_err(|e| e.to_string())? else {
+            continue;
+        };
+        let mut text = String::new();
+        patch
+            .print(&mut |_, _, line| {
+                push_patch_line(&mut text, &line);
+                true
+            })
+            .map_err(|e| e.to_string())?;
+        files.insert(normalize_repo_path(&path.to_string_lossy()), text);
+    }
+    Ok(files)
+}
+
+/// The commits a range diff compares. Resolved once so the cache key and
+/// the diff agree even when a branch moves in between.
+#[derive(Debug, Clone, Copy)]
+pub struct ResolvedRange {
+    from: Oid,
+    to: Oid,
+    merge_base: Option<Oid>,
+}
+
+pub fn resolve_range(
+    summary: &RepoSummary,
+    from: &str,
+    to: Option<&str>,
+) -> Result<ResolvedRange, String> {
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let (from, to, merge_base) = resolve_diff_range(&repo, from, to)?;
+    Ok(ResolvedRange {
+        from,
+        to,
+        merge_base,
     })
-    .map_err(|e| e.to_string())?;
+}
 
-    Ok(UnifiedDiffText { text })
+/// Cache key for a `RangeDiff`, built from the oids the range resolves to
+/// so it changes when a branch or HEAD moves.
+pub fn range_diff_cache_key(summary: &RepoSummary, range: &ResolvedRange) -> String {
+    let merge_base = range.merge_base.map(|oid| oid.to_string());
+    format!(
+        "range:{}:{}:{}:{}",
+        summary.repo_id,
+        range.from,
+        range.to,
+        merge_base.as_deref().unwrap_or("none")
+    )
+}
+
+pub fn commit_detail(summary: &RepoSummary, oid: &str) -> Result<CommitDetail, String> {
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let commit = find_commit(&repo, oid)?;
+    let files = commit_file_changes(&repo, &commit)?;
+
+    let refs = refs_by_commit(&repo);
+    Ok(CommitDetail {
+        commit: log_entry(&commit, &refs),
+        message: String::from_utf8_lossy(commit.message_bytes()).to_string(),
+        files,
+    })
+}
+
+/// Files changed by `commit` against its first parent, with hunks tagged
+/// `RepoDiffKind::Commit` so single files can be re-fetched later.
+fn commit_file_changes(
+    repo: &Repository,
+    commit: &git2::Commit,
+) -> Result<Vec<CommitFileChange>, String> {
+    let (parent_tree, tree) = commit_trees(commit)?;
+    let mut diff = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+        .map_err(|e| e.to_string())?;
+    find_renames(&mut diff)?;
+
+    let text = diff_patch_text(&diff)?;
+    let kind = RepoDiffKind::Commit {
+        oid: commit.id().to_string(),
+    };
+    let hunks = parse_diff_hunks(&text, "", kind);
+
+    let mut files = Vec::new();
+    for delta in diff.deltas() {
+        let new_path = delta.new_file().path();
+        let old_path = delta.old_file().path();
+        let Some(path) = new_path.or(old_path) else {
+            continue;
+        };
+        let path = normalize_repo_path(&path.to_string_lossy());
+        let change = file_change_kind(delta.status());
+        let old_path = renamed_from(change, old_path);
+        files.push(CommitFileChange {
+            hunks: hunks
+                .iter()
+                .filter(|hunk| hunk.path == path)
+                .cloned()
+                .collect(),
+            path,
+            old_path,
+            change,
+            binary: delta.flags().is_binary(),
+        });
+    }
+    Ok(files)
+}
+
+pub fn range_diff(summary: &RepoSummary, range: &ResolvedRange) -> Result<RangeDiff, String> {
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let ResolvedRange {
+        from: from_oid,
+        to: to_oid,
+        merge_base,
+    } = *range;
+    let old_tree = repo
+        .find_commit(from_oid)
+        .and_then(|commit| commit.tree())
+        .map_err(|e| e.to_string())?;
+    let new_tree = repo
+        .find_commit(to_oid)
+        .and_then(|commit| commit.tree())
+        .map_err(|e| e.to_string())?;
+    let mut diff = repo
+        .diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)
+        .map_err(|e| e.to_string())?;
+    find_renames(&mut diff)?;
+
+    let mut files = Vec::new();
+    for (index, delta) in diff.deltas().enumerate() {
+        let new_path = delta.new_file().path();
+        let old_path = delta.old_file().path();
+        let Some(path) = new_path.or(old_path) else {
+            continue;
+        };
+        let change = file_change_kind(delta.status());
+        let mut summary = DiffFileSummary {
+            path: normalize_repo_path(&path.to_string_lossy()),
+            old_path: renamed_from(change, old_path),
+            change,
+            binary: delta.flags().is_binary(),
+            additions: 0,
+            deletions: 0,
+        };
+        if let Ok(Some(patch)) = Patch::from_diff(&diff, index) {
+            summary.binary = patch.delta().flags().is_binary();
+            if let Ok((_, additions, deletions)) = patch.line_stats() {
+                summary.additions = additions as u32;
+                summary.deletions = deletions as u32;
+            }
+        }
+        files.push(summary);
+    }
+
+    Ok(RangeDiff {
+        from: from_oid.to_string(),
+        to: to_oid.to_string(),
+        merge_base: merge_base.map(|oid| oid.to_string()),
+        files,
+    })
+}
+
+/// Resolves `from`/`to` to commits. Without `to`, `from` may be a two-dot
+/// range, a three-dot range (diffed from the merge-base) or a single
+/// revision compared against HEAD.
+fn resolve_diff_range(
+    repo: &Repository,
+    from: &str,
+    to: Option<&str>,
+) -> Result<(Oid, Oid, Option<Oid>), String> {
+    if let Some(to) = to.map(str::trim).filter(|value| !value.is_empty()) {
+        let from_oid = find_commit(repo, from.trim())?.id();
+        let to_oid = find_commit(repo, to)?.id();
+        return Ok((from_oid, to_oid, None));
+    }
+
+    let spec = repo.revparse(from.trim()).map_err(|e| e.to_string())?;
+    if spec.mode().contains(RevparseMode::SINGLE) {
+        let from_oid = revspec_commit(spec.from(), from)?;
+        let to_oid = find_commit(repo, "HEAD")?.id();
+        return Ok((from_oid, to_oid, None));
+    }
+
+    let from_oid = revspec_commit(spec.from(), from)?;
+    let to_oid = revspec_commit(spec.to(), from)?;
+    if spec.mode().contains(RevparseMode::MERGE_BASE) {
+        let base = repo.merge_base(from_oid, to_oid).map_err(|e| e.to_string())?;
+        return Ok((base, to_oid, Some(base)));
+    }
+    Ok((from_oid, to_oid, None))
+}
+
+fn revspec_commit(object: Option<&git2::Object>, revision: &str) -> Result<Oid, String> {
+    object
+        .ok_or_else(|| format!("invalid revision: {revision}"))?
+        .peel_to_commit()
+        .map(|commit| commit.id())
+        .map_err(|e| e.to_string())
+}
+
+fn file_change_kind(status: Delta) -> FileChangeKind {
+    match status {
+        Delta::Added => FileChangeKind::Added,
+        Delta::Deleted => FileChangeKind::Deleted,
+        Delta::Renamed => FileChangeKind::Renamed,
+        Delta::Copied => FileChangeKind::Copied,
+        Delta::Typechange => FileChangeKind::TypeChange,
+        _ => FileChangeKind::Modified,
+    }
+}
+
+fn renamed_from(change: FileChangeKind, old_path: Option<&Path>) -> Option<String> {
+    if !matches!(change, FileChangeKind::Renamed | FileChangeKind::Copied) {
+        return None;
+    }
+    old_path.map(|value| normalize_repo_path(&value.to_string_lossy()))
+}
+
+fn find_commit<'repo>(
+    repo: &'repo Repository,
+    revision: &str,
+) -> Result<git2::Commit<'repo>, String> {
+    repo.revparse_single(revision)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| e.to_string())
+}
+
+/// The first parent's tree (none for a root commit) and the commit's tree.
+fn commit_trees<'repo>(
+    commit: &git2::Commit<'repo>,
+) -> Result<(Option<git2::Tree<'repo>>, git2::Tree<'repo>), String> {
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree().map_err(|e| e.to_string())?),
+        Err(_) => None,
+    };
+    let tree = commit.tree().map_err(|e| e.to_string())?;
+    Ok((parent_tree, tree))
 }
 
 pub fn diff_hunks_for_path(
@@ -582,18 +1824,138 @@ fn remove_index_path(index: &mut git2::Index, path: &Path) -> Result<(), String>
     index.write().map_err(|e| e.to_string())
 }
 
+pub fn conflict_versions(summary: &RepoSummary, path: &str) -> Result<ConflictVersions, String> {
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let normalized_path = normalize_repo_path(path);
+    let index = repo.index().map_err(|e| e.to_string())?;
+    let stage = |stage: i32| -> Result<Option<ConflictBlob>, String> {
+        let Some(entry) = index.get_path(Path::new(&normalized_path), stage) else {
+            return Ok(None);
+        };
+        let blob = repo.find_blob(entry.id).map_err(|e| e.to_string())?;
+        let binary = blob.is_binary();
+        Ok(Some(ConflictBlob {
+            oid: entry.id.to_string(),
+            path: String::from_utf8_lossy(&entry.path).to_string(),
+            binary,
+            content: if binary {
+                None
+            } else {
+                Some(String::from_utf8_lossy(blob.content()).to_string())
+            },
+        }))
+    };
+
+    let versions = ConflictVersions {
+        path: normalized_path.clone(),
+        base: stage(1)?,
+        ours: stage(2)?,
+        theirs: stage(3)?,
+    };
+    if versions.base.is_none() && versions.ours.is_none() && versions.theirs.is_none() {
+        return Err(format!("{normalized_path} is not conflicted"));
+    }
+    Ok(versions)
+}
+
+/// Writes the chosen side (or merged text) to the working tree and marks the
+/// path resolved. Taking a side that deleted the file removes it.
+pub fn resolve_conflict(
+    summary: &RepoSummary,
+    path: &str,
+    resolution: &ConflictResolution,
+) -> Result<(), String> {
+    let versions = conflict_versions(summary, path)?;
+    let file_path = Path::new(&summary.worktree_path).join(&versions.path);
+    let content = match resolution {
+        ConflictResolution::Ours => side_content(summary, versions.ours.as_ref())?,
+        ConflictResolution::Theirs => side_content(summary, versions.theirs.as_ref())?,
+        ConflictResolution::Merged { content } => Some(content.as_bytes().to_vec()),
+    };
+
+    match content {
+        Some(bytes) => {
+            if let Some(parent) = file_path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            std::fs::write(&file_path, bytes).map_err(|e| e.to_string())?;
+        }
+        None => {
+            if file_path.exists() {
+                std::fs::remove_file(&file_path).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    mark_conflict_resolved(summary, &versions.path)
+}
+
+fn side_content(
+    summary: &RepoSummary,
+    side: Option<&ConflictBlob>,
+) -> Result<Option<Vec<u8>>, String> {
+    let Some(side) = side else {
+        return Ok(None);
+    };
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let oid = Oid::from_str(&side.oid).map_err(|e| e.to_string())?;
+    let blob = repo.find_blob(oid).map_err(|e| e.to_string())?;
+    Ok(Some(blob.content().to_vec()))
+}
+
+/// Clears the conflict stages for `path` and stages the working-tree file,
+/// or removes the path when the file no longer exists.
+pub fn mark_conflict_resolved(summary: &RepoSummary, path: &str) -> Result<(), String> {
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let normalized_path = normalize_repo_path(path);
+    let repo_path = Path::new(&normalized_path);
+    let mut index = repo.index().map_err(|e| e.to_string())?;
+    for stage in 1..=3 {
+        if index.get_path(repo_path, stage).is_some() {
+            index.remove(repo_path, stage).map_err(|e| e.to_string())?;
+        }
+    }
+    if Path::new(&summary.worktree_path).join(repo_path).exists() {
+        index.add_path(repo_path).map_err(|e| e.to_string())?;
+    } else {
+        index.remove_path(repo_path).map_err(|e| e.to_string())?;
+    }
+    index.write().map_err(|e| e.to_string())
+}
+
 pub fn list_branches(summary: &RepoSummary) -> Result<BranchList, String> {
     let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
     let head = repo_head(&repo)?;
-    let current = head.branch_name.clone();
+    let current = head.branch_name.clone().unwrap_or_default();
     let mut locals = Vec::new();
     let mut remotes = Vec::new();
+    let mut ahead_behind = std::collections::HashMap::new();
+    let mut upstreams = std::collections::HashMap::new();
 
     if let Ok(iter) = repo.branches(Some(BranchType::Local)) {
-        for item in iter.flatten() {
-            if let Ok(Some(name)) = item.0.name() {
-                locals.push(name.to_string());
+        for (branch, _) in iter.flatten() {
+            let Ok(Some(name)) = branch.name() else {
+                continue;
+            };
+            let name = name.to_string();
+            if let Ok(upstream) = branch.upstream() {
+                if let Ok(Some(upstream_name)) = upstream.name() {
+                    upstreams.insert(name.clone(), upstream_name.to_string());
+                }
+                if let (Some(local_oid), Some(upstream_oid)) =
+                    (branch.get().target(), upstream.get().target())
+                {
+                    if let Ok((ahead, behind)) = repo.graph_ahead_behind(local_oid, upstream_oid) {
+                        ahead_behind.insert(
+                            name.clone(),
+                            AheadBehind {
+                                ahead: ahead as u32,
+                                behind: behind as u32,
+                            },
+                        );
+                    }
+                }
             }
+            locals.push(name);
         }
     }
 
@@ -608,617 +1970,2988 @@ pub fn list_branches(summary: &RepoSummary) -> Result<BranchList, String> {
     locals.sort();
     remotes.sort();
 
+    // Tags have their own command; a bad tag must not hide the branches.
+    let tags = tag_infos(&repo).unwrap_or_else(|error| {
+        tracing::warn!(repo_id = %summary.repo_id, error = %error, "failed to list tags");
+        Vec::new()
+    });
+
     Ok(BranchList {
         current,
         locals,
         remotes,
-        ahead_behind: None,
+        ahead_behind: Some(ahead_behind),
+        upstreams,
+        tags,
     })
 }
 
-pub fn checkout_branch(
-    summary: &RepoSummary,
-    target: &CheckoutTarget,
-) -> Result<CheckoutResult, RepoError> {
-    let repo = Repository::open(&summary.path).map_err(|e| RepoError::GitError {
-        message: e.to_string(),
-    })?;
+pub fn list_tags(summary: &RepoSummary) -> Result<Vec<TagInfo>, String> {
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    tag_infos(&repo)
+}
 
-    if is_workdir_dirty(&repo) {
-        return Err(RepoError::DirtyWorkingTree {
-            message: "Working tree has uncommitted changes.".to_string(),
-        });
-    }
+fn tag_infos(repo: &Repository) -> Result<Vec<TagInfo>, String> {
+    let mut refs = Vec::new();
+    repo.tag_foreach(|oid, name| {
+        refs.push((oid, String::from_utf8_lossy(name).to_string()));
+        true
+    })
+    .map_err(|e| e.to_string())?;
 
-    match target.kind {
-        CheckoutTargetKind::Local => checkout_local(&repo, &target.name)?,
-        CheckoutTargetKind::Remote => checkout_remote(&repo, &target.name)?,
+    let mut tags = Vec::new();
+    for (oid, reference) in refs {
+        let Ok(object) = repo.find_object(oid, None) else {
+            continue;
+        };
+        let Ok(commit) = object.peel_to_commit() else {
+            continue;
+        };
+        let name = reference.trim_start_matches("refs/tags/").to_string();
+        let tag = object.as_tag();
+        tags.push(TagInfo {
+            name,
+            target: commit.id().to_string(),
+            annotated: tag.is_some(),
+            message: tag
+                .and_then(|tag| tag.message())
+                .map(|message| message.trim_end().to_string()),
+            tagger: tag.and_then(|tag| tag.tagger()).map(|sig| commit_signature(&sig)),
+        });
     }
-
-    let head = repo_head(&repo).map_err(|e| RepoError::GitError { message: e })?;
-    Ok(CheckoutResult { head })
+    tags.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(tags)
 }
 
-pub fn create_branch(
+/// Creates a tag at `revision` (HEAD by default); a message makes it an
+/// annotated tag with the configured identity as tagger.
+pub fn create_tag(
     summary: &RepoSummary,
     name: &str,
-    from: Option<&str>,
-) -> Result<(), String> {
+    revision: Option<&str>,
+    message: Option<&str>,
+) -> Result<TagInfo, String> {
     let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
-    let target = if let Some(from) = from {
-        repo.revparse_single(from).map_err(|e| e.to_string())?
-    } else {
-        repo.head()
-            .and_then(|h| h.peel(ObjectType::Commit))
-            .map_err(|e| e.to_string())?
-    };
-
-    let commit = target
-        .peel(ObjectType::Commit)
-        .map_err(|e| e.to_string())?
-        .into_commit()
-        .map_err(|_| "not a commit".to_string())?;
-
-    repo.branch(name, &commit, false)
+    let commit = repo
+        .revparse_single(revision.unwrap_or("HEAD"))
+        .and_then(|obj| obj.peel_to_commit())
         .map_err(|e| e.to_string())?;
-    Ok(())
+    if repo.find_reference(&format!("refs/tags/{name}")).is_ok() {
+        return Err(format!("Tag {name} already exists."));
+    }
+
+    match message.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(message) => {
+            let signature = repo.signature().map_err(|e| e.to_string())?;
+            repo.tag(name, commit.as_object(), &signature, message, false)
+                .map_err(|e| e.to_string())?;
+        }
+        None => {
+            repo.tag_lightweight(name, commit.as_object(), false)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    tag_infos(&repo)?
+        .into_iter()
+        .find(|tag| tag.name == name)
+        .ok_or_else(|| "tag not found".to_string())
 }
 
-pub fn fetch(summary: &RepoSummary, remote: Option<&str>) -> Result<bool, String> {
+pub fn delete_tag(summary: &RepoSummary, name: &str) -> Result<(), String> {
     let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
-    let remote_name = remote.unwrap_or("origin");
-    let mut remote = repo.find_remote(remote_name).map_err(|e| e.to_string())?;
+    repo.tag_delete(name).map_err(|e| e.to_string())
+}
 
-    let mut callbacks = RemoteCallbacks::new();
-    callbacks.credentials(|_url, _username, _allowed| git2::Cred::default());
+/// Pushes one tag, or every tag when `name` is `None`.
+pub fn push_tags(
+    summary: &RepoSummary,
+    remote: Option<&str>,
+    name: Option<&str>,
+    credentials: Option<&CredentialInput>,
+) -> Result<(), RepoError> {
+    let repo = Repository::open(&summary.path).map_err(|e| RepoError::GitError {
+        message: e.to_string(),
+    })?;
+    let names = match name {
+        Some(name) => vec![name.to_string()],
+        None => tag_infos(&repo)
+            .map_err(|message| RepoError::GitError { message })?
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect(),
+    };
+    if names.is_empty() {
+        return Ok(());
+    }
+    let refspecs: Vec<String> = names
+        .iter()
+        .map(|name| format!("refs/tags/{name}:refs/tags/{name}"))
+        .collect();
+    let head_branch = repo
+        .head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand().map(str::to_string));
+    let remote_name = push_remote(&repo, remote, head_branch.as_deref());
+    push_refspecs(&repo, &remote_name, &refspecs, credentials)
+}
 
-    let mut options = FetchOptions::new();
-    options.remote_callbacks(callbacks);
+pub fn checkout_branch(
+    summary: &RepoSummary,
+    target: &CheckoutTarget,
+    mode: CheckoutMode,
+) -> Result<CheckoutResult, RepoError> {
+    let repo = Repository::open(&summary.path).map_err(|e| RepoError::GitError {
+        message: e.to_string(),
+    })?;
+    let dirty = is_workdir_dirty(&repo);
+
+    match mode {
+        CheckoutMode::Clean if dirty => Err(RepoError::DirtyWorkingTree {
+            message: "Working tree has uncommitted changes.".to_string(),
+        }),
+        CheckoutMode::Stash if dirty => {
+            drop(repo);
+            checkout_with_stash(summary, target)
+        }
+        _ => {
+            checkout_target(&repo, target)?;
+            let head = repo_head(&repo).map_err(|e| RepoError::GitError { message: e })?;
+            Ok(CheckoutResult {
+                head,
+                conflicts: Vec::new(),
+                stash: None,
+            })
+        }
+    }
+}
+
+/// Stashes local changes (including untracked files), switches and pops the
+/// stash again. A conflicting pop leaves the stash in place and reports the
+/// conflicted paths instead of failing the checkout.
+fn checkout_with_stash(
+    summary: &RepoSummary,
+    target: &CheckoutTarget,
+) -> Result<CheckoutResult, RepoError> {
+    let message = format!("gitpanel: checkout {}", target.name);
+    let stash = stash_save(summary, Some(&message), true, false)
+        .map_err(|message| RepoError::GitError { message })?;
+
+    let repo = Repository::open(&summary.path).map_err(|e| RepoError::GitError {
+        message: e.to_string(),
+    })?;
+    if let Err(error) = checkout_target(&repo, target) {
+        if let Err(pop_error) = stash_pop(summary, stash.index, &stash.oid) {
+            return Err(RepoError::StashRestoreFailed {
+                message: format!(
+                    "Checkout failed ({}) and restoring local changes also failed ({}); \
+                     they remain in stash@{{{}}}.",
+                    error.message(),
+                    pop_error.message(),
+                    stash.index
+                ),
+                stash,
+            });
+        }
+        return Err(error);
+    }
+    let head = repo_head(&repo).map_err(|e| RepoError::GitError { message: e })?;
+
+    match stash_pop(summary, stash.index, &stash.oid) {
+        Ok(()) => Ok(CheckoutResult {
+            head,
+            conflicts: Vec::new(),
+            stash: None,
+        }),
+        Err(RepoError::MergeConflict { paths, .. }) => Ok(CheckoutResult {
+            head,
+            conflicts: paths,
+            stash: Some(stash),
+        }),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn create_branch(
+    summary: &RepoSummary,
+    name: &str,
+    from: Option<&str>,
+    checkout: bool,
+) -> Result<Option<RepoHead>, String> {
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let target = if let Some(from) = from {
+        repo.revparse_single(from).map_err(|e| e.to_string())?
+    } else {
+        repo.head()
+            .and_then(|h| h.peel(ObjectType::Commit))
+            .map_err(|e| e.to_string())?
+    };
+
+    let commit = target
+        .peel(ObjectType::Commit)
+        .map_err(|e| e.to_string())?
+        .into_commit()
+        .map_err(|_| "not a commit".to_string())?;
 
-    remote
-        .fetch(&[] as &[&str], Some(&mut options), None)
+    repo.branch(name, &commit, false)
         .map_err(|e| e.to_string())?;
+    if !checkout {
+        return Ok(None);
+    }
 
-    Ok(true)
+    let target = CheckoutTarget {
+        kind: CheckoutTargetKind::Local,
+        name: name.to_string(),
+    };
+    checkout_target(&repo, &target).map_err(|error| error.message().to_string())?;
+    repo_head(&repo).map(Some)
 }
 
-pub fn list_worktrees(repo_root: &str) -> Result<WorktreeList, String> {
-    let output = run_git(repo_root, &["worktree", "list", "--porcelain"], None)?;
-    let mut worktrees = Vec::new();
-    let mut current_path = None;
-    let mut current_head = None;
-    let mut current_branch = None;
+/// Deletes a local branch. Without `force` the branch must be merged into
+/// its upstream, or into HEAD when it has none, like `git branch -d`.
+pub fn delete_branch(summary: &RepoSummary, name: &str, force: bool) -> Result<(), RepoError> {
+    let git_error = |message: String| RepoError::GitError { message };
+    let repo = Repository::open(&summary.path).map_err(|e| git_error(e.to_string()))?;
+    let mut branch = repo
+        .find_branch(name, BranchType::Local)
+        .map_err(|e| git_error(e.to_string()))?;
+    if branch.is_head() {
+        return Err(RepoError::BranchCheckedOut {
+            message: format!("Branch {name} is checked out."),
+            branch: name.to_string(),
+        });
+    }
 
-    for line in output.lines() {
-        if line.starts_with("worktree ") {
-            if let Some(path) = current_path.take() {
-                worktrees.push(WorktreeInfo {
-                    path,
-                    head: current_head.take().unwrap_or_else(|| "unknown".to_string()),
-                    branch: current_branch.take().unwrap_or_else(|| "DETACHED".to_string()),
-                });
-            }
-            current_path = Some(line.trim_start_matches("worktree ").trim().to_string());
-        } else if line.starts_with("HEAD ") {
-            current_head = Some(line.trim_start_matches("HEAD ").trim().to_string());
-        } else if line.starts_with("branch ") {
-            let branch = line.trim_start_matches("branch ").trim().to_string();
-            current_branch = Some(branch.trim_start_matches("refs/heads/").to_string());
+    if !force {
+        let tip = branch
+            .get()
+            .target()
+            .ok_or_else(|| git_error("branch has no target".to_string()))?;
+        let base = match branch.upstream() {
+            Ok(upstream) => upstream.get().target(),
+            Err(_) => repo.head().ok().and_then(|head| head.target()),
+        };
+        let merged = base.is_some_and(|base| {
+            base == tip || repo.graph_descendant_of(base, tip).unwrap_or(false)
+        });
+        if !merged {
+            return Err(RepoError::BranchNotMerged {
+                message: format!("Branch {name} is not fully merged."),
+                branch: name.to_string(),
+            });
         }
     }
 
-    if let Some(path) = current_path.take() {
-        worktrees.push(WorktreeInfo {
-            path,
-            head: current_head.take().unwrap_or_else(|| "unknown".to_string()),
-            branch: current_branch.take().unwrap_or_else(|| "DETACHED".to_string()),
+    branch.delete().map_err(|e| git_error(e.to_string()))
+}
+
+/// Renames a local branch. libgit2 moves HEAD and the branch's config
+/// section (including its upstream) along with it; changelist state is keyed
+/// by path rather than branch, so it needs no update.
+pub fn rename_branch(summary: &RepoSummary, name: &str, new_name: &str) -> Result<(), RepoError> {
+    let git_error = |message: String| RepoError::GitError { message };
+    let repo = Repository::open(&summary.path).map_err(|e| git_error(e.to_string()))?;
+    if repo.find_branch(new_name, BranchType::Local).is_ok() {
+        return Err(RepoError::BranchExists {
+            message: format!("Branch {new_name} already exists."),
+            branch: new_name.to_string(),
         });
     }
+    let mut branch = repo
+        .find_branch(name, BranchType::Local)
+        .map_err(|e| git_error(e.to_string()))?;
+    branch
+        .rename(new_name, false)
+        .map(|_| ())
+        .map_err(|e| git_error(e.to_string()))
+}
 
-    Ok(WorktreeList {
-        repo_root: repo_root.to_string(),
-        worktrees,
-    })
+pub fn set_upstream(
+    summary: &RepoSummary,
+    name: &str,
+    upstream: Option<&str>,
+) -> Result<(), RepoError> {
+    let git_error = |message: String| RepoError::GitError { message };
+    let repo = Repository::open(&summary.path).map_err(|e| git_error(e.to_string()))?;
+    let mut branch = repo
+        .find_branch(name, BranchType::Local)
+        .map_err(|e| git_error(e.to_string()))?;
+    if let Some(upstream) = upstream {
+        repo.find_branch(upstream, BranchType::Remote)
+            .map_err(|e| git_error(e.to_string()))?;
+    }
+    branch
+        .set_upstream(upstream)
+        .map_err(|e| git_error(e.to_string()))
 }
 
-pub fn add_worktree(
-    repo_root: &str,
-    path: &str,
-    branch_name: &str,
-    new_branch: bool,
-) -> Result<WorktreeResult, String> {
-    let mut args = vec!["worktree", "add"];
-    if new_branch {
-        args.push("-b");
-        args.push(branch_name);
-        args.push(path);
-        args.push("HEAD");
-    } else {
-        args.push(path);
-        args.push(branch_name);
+/// Pushes a deletion of `branch` to `remote` and drops the matching
+/// remote-tracking branch.
+pub fn delete_remote_branch(
+    summary: &RepoSummary,
+    remote: Option<&str>,
+    branch: &str,
+    credentials: Option<&CredentialInput>,
+) -> Result<(), RepoError> {
+    let git_error = |message: String| RepoError::GitError { message };
+    let repo = Repository::open(&summary.path).map_err(|e| git_error(e.to_string()))?;
+    let remote_name = push_remote(&repo, remote, Some(branch));
+    let refspec = format!(":refs/heads/{branch}");
+    push_refspecs(&repo, &remote_name, &[refspec], credentials)?;
+
+    let tracking_name = format!("{remote_name}/{branch}");
+    if let Ok(mut tracking) = repo.find_branch(&tracking_name, BranchType::Remote) {
+        tracking.delete().map_err(|e| git_error(e.to_string()))?;
     }
-    run_git(repo_root, &args, None)?;
-    Ok(WorktreeResult {
-        ok: true,
-        message: "worktree added".to_string(),
-    })
+    Ok(())
 }
 
-pub fn remove_worktree(repo_root: &str, path: &str) -> Result<WorktreeResult, String> {
-    run_git(repo_root, &["worktree", "remove", path], None)?;
-    Ok(WorktreeResult {
-        ok: true,
-        message: "worktree removed".to_string(),
-    })
+pub fn fetch(
+    summary: &RepoSummary,
+    remote: Option<&str>,
+    credentials: Option<&CredentialInput>,
+) -> Result<bool, RepoError> {
+    let git_error = |message: String| RepoError::GitError { message };
+    let repo = Repository::open(&summary.path).map_err(|e| git_error(e.to_string()))?;
+    let remote_name = remote.unwrap_or("origin");
+    let mut remote = repo
+        .find_remote(remote_name)
+        .map_err(|e| git_error(e.to_string()))?;
+
+    let provider = CredentialProvider::new(&repo, credentials.cloned());
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(|url, username, allowed| provider.credentials(url, username, allowed));
+
+    let mut options = FetchOptions::new();
+    options.remote_callbacks(callbacks);
+
+    let fetched = remote.fetch(&[] as &[&str], Some(&mut options), None);
+    drop(options);
+    fetched.map_err(|error| provider.map_error(error))?;
+
+    Ok(true)
 }
 
-pub fn prune_worktrees(repo_root: &str) -> Result<WorktreeResult, String> {
-    run_git(repo_root, &["worktree", "prune"], None)?;
-    Ok(WorktreeResult {
-        ok: true,
-        message: "worktrees pruned".to_string(),
+pub fn pull(
+    summary: &RepoSummary,
+    strategy: PullStrategy,
+    credentials: Option<&CredentialInput>,
+) -> Result<PullResult, RepoError> {
+    let git_error = |message: String| RepoError::GitError { message };
+    let repo = Repository::open(&summary.path).map_err(|e| git_error(e.to_string()))?;
+    let head = repo.head().map_err(|e| git_error(e.to_string()))?;
+    if !head.is_branch() {
+        return Err(git_error("HEAD is not on a branch.".to_string()));
+    }
+    let head_ref = head
+        .name()
+        .ok_or_else(|| git_error("invalid branch name".to_string()))?
+        .to_string();
+    let old_oid = head
+        .target()
+        .ok_or_else(|| git_error("HEAD has no target".to_string()))?;
+    let remote_name = repo
+        .branch_upstream_remote(&head_ref)
+        .ok()
+        .and_then(|buf| buf.as_str().map(|value| value.to_string()))
+        .ok_or_else(|| git_error("Current branch has no upstream configured.".to_string()))?;
+
+    fetch(summary, Some(&remote_name), credentials)?;
+
+    let upstream = repo
+        .find_branch(head.shorthand().unwrap_or_default(), BranchType::Local)
+        .and_then(|branch| branch.upstream())
+        .map_err(|e| git_error(e.to_string()))?;
+    let upstream_name = upstream
+        .name()
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+        .to_string();
+    let upstream_ref = upstream
+        .get()
+        .name()
+        .ok_or_else(|| git_error("invalid upstream name".to_string()))?
+        .to_string();
+
+    let args: Vec<&str> = match strategy {
+        PullStrategy::FastForward => vec!["merge", "--ff-only", &upstream_ref],
+        PullStrategy::Merge => vec!["merge", "--no-edit", &upstream_ref],
+        PullStrategy::Rebase => vec!["rebase", &upstream_ref],
+    };
+    if let Err(message) = run_git(&summary.path, &args, None) {
+        let paths = conflicted_paths(&repo).map_err(git_error)?;
+        if paths.is_empty() {
+            return Err(git_error(message));
+        }
+        return Err(RepoError::MergeConflict {
+            message: format!("Pulling {upstream_name} produced conflicts."),
+            paths,
+        });
+    }
+
+    let new_oid = repo
+        .refname_to_id("HEAD")
+        .map_err(|e| git_error(e.to_string()))?;
+    let updated_files = changed_paths_between(&repo, old_oid, new_oid).map_err(git_error)?;
+    let head = repo_head(&repo).map_err(git_error)?;
+    Ok(PullResult {
+        head,
+        upstream: upstream_name,
+        updated_files,
     })
 }
 
-pub fn repo_id_for_path(path: &str) -> RepoId {
-    let mut hasher = DefaultHasher::new();
-    path.hash(&mut hasher);
-    format!("{:x}", hasher.finish())
+fn changed_paths_between(
+    repo: &Repository,
+    old_oid: git2::Oid,
+    new_oid: git2::Oid,
+) -> Result<Vec<String>, String> {
+    if old_oid == new_oid {
+        return Ok(Vec::new());
+    }
+    let old_tree = repo
+        .find_commit(old_oid)
+        .and_then(|commit| commit.tree())
+        .map_err(|e| e.to_string())?;
+    let new_tree = repo
+        .find_commit(new_oid)
+        .and_then(|commit| commit.tree())
+        .map_err(|e| e.to_string())?;
+    let diff = repo
+        .diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)
+        .map_err(|e| e.to_string())?;
+    let mut paths = Vec::new();
+    for delta in diff.deltas() {
+        if let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) {
+            paths.push(path.to_string_lossy().to_string());
+        }
+    }
+    Ok(paths)
 }
 
-fn repo_head(repo: &Repository) -> Result<RepoHead, String> {
-    match repo.head() {
-        Ok(head) => {
-            let branch_name = head
-                .shorthand()
-                .unwrap_or("DETACHED")
-                .to_string();
-            let oid_short = head
-                .target()
-                .map(|oid| oid.to_string())
-                .map(|oid| oid.chars().take(7).collect::<String>())
-                .unwrap_or_else(|| "unknown".to_string());
-
-            Ok(RepoHead {
-                branch_name,
-                oid_short,
-            })
+pub fn push(
+    summary: &RepoSummary,
+    remote: Option<&str>,
+    branch: Option<&str>,
+    set_upstream: bool,
+    credentials: Option<&CredentialInput>,
+) -> Result<PushResult, RepoError> {
+    let git_error = |message: String| RepoError::GitError { message };
+    let repo = Repository::open(&summary.path).map_err(|e| git_error(e.to_string()))?;
+    let branch_name = match branch {
+        Some(name) => name.to_string(),
+        None => {
+            let head = repo.head().map_err(|e| git_error(e.to_string()))?;
+            if !head.is_branch() {
+                return Err(git_error("HEAD is not on a branch.".to_string()));
+            }
+            head.shorthand()
+                .ok_or_else(|| git_error("invalid branch name".to_string()))?
+                .to_string()
         }
-        Err(_) => Ok(RepoHead {
-            branch_name: "NO-HEAD".to_string(),
-            oid_short: "—".to_string(),
-        }),
+    };
+    repo.find_branch(&branch_name, BranchType::Local)
+        .map_err(|e| git_error(e.to_string()))?;
+    let remote_name = push_remote(&repo, remote, Some(&branch_name));
+    let destination = remote
+        .is_none()
+        .then(|| upstream_merge_ref(&repo, &branch_name, &remote_name))
+        .flatten()
+        .unwrap_or_else(|| format!("refs/heads/{branch_name}"));
+    let refspec = format!("refs/heads/{branch_name}:{destination}");
+    push_refspecs(&repo, &remote_name, &[refspec], credentials)?;
+
+    let mut upstream = None;
+    if set_upstream {
+        let mut local = repo
+            .find_branch(&branch_name, BranchType::Local)
+            .map_err(|e| git_error(e.to_string()))?;
+        let remote_branch = destination
+            .strip_prefix("refs/heads/")
+            .unwrap_or(&destination);
+        let upstream_name = format!("{remote_name}/{remote_branch}");
+        local
+            .set_upstream(Some(&upstream_name))
+            .map_err(|e| git_error(e.to_string()))?;
+        upstream = Some(upstream_name);
     }
+
+    Ok(PushResult {
+        remote: remote_name,
+        branch: branch_name,
+        upstream,
+    })
 }
 
-fn is_index_change(status: Status) -> bool {
-    status.intersects(
-        Status::INDEX_NEW
-            | Status::INDEX_MODIFIED
-            | Status::INDEX_DELETED
-            | Status::INDEX_RENAMED
-            | Status::INDEX_TYPECHANGE,
-    )
+/// Picks the remote to push to the way git does: an explicit remote, then
+/// `branch.<name>.pushRemote`, `remote.pushDefault`, `branch.<name>.remote`
+/// and finally `origin`.
+fn push_remote(repo: &Repository, remote: Option<&str>, branch: Option<&str>) -> String {
+    if let Some(remote) = remote {
+        return remote.to_string();
+    }
+    let config = repo.config().ok();
+    let config_value = |key: &str| {
+        config
+            .as_ref()
+            .and_then(|config| config.get_string(key).ok())
+            .filter(|value| !value.is_empty())
+    };
+    branch
+        .and_then(|branch| config_value(&format!("branch.{branch}.pushRemote")))
+        .or_else(|| config_value("remote.pushDefault"))
+        .or_else(|| branch.and_then(|branch| config_value(&format!("branch.{branch}.remote"))))
+        .filter(|name| name != ".")
+        .unwrap_or_else(|| "origin".to_string())
 }
 
-fn is_workdir_change(status: Status) -> bool {
-    status.intersects(
-        Status::WT_MODIFIED
-            | Status::WT_DELETED
-            | Status::WT_RENAMED
-            | Status::WT_TYPECHANGE,
-    )
+/// Returns `branch.<name>.merge` when the branch tracks a ref on
+/// `remote_name`, so pushes update the upstream like `push.default=simple`.
+fn upstream_merge_ref(repo: &Repository, branch: &str, remote_name: &str) -> Option<String> {
+    let config = repo.config().ok()?;
+    let tracked_remote = config.get_string(&format!("branch.{branch}.remote")).ok()?;
+    if tracked_remote != remote_name {
+        return None;
+    }
+    config
+        .get_string(&format!("branch.{branch}.merge"))
+        .ok()
+        .filter(|merge| merge.starts_with("refs/heads/"))
 }
 
-fn extract_paths(entry: &git2::StatusEntry) -> (Option<String>, Option<String>) {
-    let mut path: Option<PathBuf> = None;
-    let mut old_path: Option<PathBuf> = None;
+/// Pushes `refspecs`, reporting per-reference rejections as `PushRejected`
+/// and exhausted credentials as `AuthRequired`. libgit2 does not expand
+/// wildcard refspecs on push, so every reference must be spelled out.
+fn push_refspecs(
+    repo: &Repository,
+    remote_name: &str,
+    refspecs: &[String],
+    credentials: Option<&CredentialInput>,
+) -> Result<(), RepoError> {
+    let git_error = |message: String| RepoError::GitError { message };
+    let mut remote = repo
+        .find_remote(remote_name)
+        .map_err(|e| git_error(e.to_string()))?;
+
+    let provider = CredentialProvider::new(repo, credentials.cloned());
+    let rejections = std::cell::RefCell::new(Vec::new());
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(|url, username, allowed| provider.credentials(url, username, allowed));
+    callbacks.push_update_reference(|reference, status| {
+        if let Some(message) = status {
+            rejections
+                .borrow_mut()
+                .push(push_rejection(reference, message));
+        }
+        Ok(())
+    });
 
-    if let Some(delta) = entry.head_to_index() {
-        if let Some(new_file) = delta.new_file().path() {
-            path = Some(new_file.to_path_buf());
+    let mut options = PushOptions::new();
+    options.remote_callbacks(callbacks);
+
+    let pushed = remote.push(refspecs, Some(&mut options));
+    drop(options);
+    let mut rejections = rejections.into_inner();
+    if let Err(error) = pushed {
+        if rejections.is_empty() && error.code() != ErrorCode::NotFastForward {
+            return Err(provider.map_error(error));
         }
-        if let Some(old_file) = delta.old_file().path() {
-            old_path = Some(old_file.to_path_buf());
+        // The local transport refuses non-fast-forward updates before any
+        // per-reference status is reported.
+        if rejections.is_empty() {
+            let refspec = refspecs.first().map(String::as_str).unwrap_or_default();
+            rejections.push(PushRejection {
+                reference: refspec.rsplit(':').next().unwrap_or(refspec).to_string(),
+                reason: PushRejectReason::NonFastForward,
+                message: error.message().to_string(),
+            });
         }
     }
-
-    if path.is_none() {
-        if let Some(delta) = entry.index_to_workdir() {
-            if let Some(new_file) = delta.new_file().path() {
-                path = Some(new_file.to_path_buf());
-            }
-            if let Some(old_file) = delta.old_file().path() {
-                old_path = Some(old_file.to_path_buf());
-            }
-        }
+    if !rejections.is_empty() {
+        return Err(RepoError::PushRejected {
+            message: format!("Push to {remote_name} was rejected."),
+            rejections,
+        });
     }
-
-    (
-        path.map(|p| p.to_string_lossy().to_string()),
-        old_path.map(|p| p.to_string_lossy().to_string()),
-    )
+    Ok(())
 }
 
-fn parse_diff_hunks(diff_text: &str, default_path: &str, kind: RepoDiffKind) -> Vec<DiffHunk> {
-    let mut hunks = Vec::new();
-    let lines: Vec<&str> = diff_text.lines().collect();
-    let mut i = 0;
-    let mut file_header: Vec<String> = Vec::new();
-    let mut current_path: Option<String> = None;
+fn push_rejection(reference: &str, message: &str) -> PushRejection {
+    let lowered = message.to_ascii_lowercase();
+    let reason = if lowered.contains("fast-forward")
+        || lowered.contains("fastforward")
+        || lowered.contains("fetch first")
+    {
+        PushRejectReason::NonFastForward
+    } else if lowered.contains("hook") {
+        PushRejectReason::HookDeclined
+    } else {
+        PushRejectReason::Other
+    };
+    PushRejection {
+        reference: reference.to_string(),
+        reason,
+        message: message.to_string(),
+    }
+}
 
-    while i < lines.len() {
-        let line = lines[i];
-        if line.starts_with("diff --git ") {
-            file_header.clear();
-            file_header.push(line.to_string());
-            current_path = extract_b_path(line);
-            i += 1;
+pub fn log(summary: &RepoSummary, req: &RepoLogRequest) -> Result<LogPage, String> {
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+    revwalk
+        .set_sorting(Sort::TOPOLOGICAL | Sort::TIME)
+        .map_err(|e| e.to_string())?;
+    push_revision(&repo, &mut revwalk, req.revision.as_deref())?;
+
+    let cursor = page_cursor(&repo, req.cursor.as_deref())?;
+    let limit = page_limit(req.limit);
+    let path = req
+        .path
+        .as_deref()
+        .map(normalize_repo_path)
+        .filter(|value| !value.is_empty());
+    let author = lowercase_filter(req.author.as_deref());
+    let message = lowercase_filter(req.message.as_deref());
+    let refs = refs_by_commit(&repo);
+
+    let mut skipping = cursor.is_some();
+    let mut entries = Vec::new();
+    let mut next_cursor = None;
+    for oid in revwalk {
+        let oid = oid.map_err(|e| e.to_string())?;
+        if skipping {
+            skipping = Some(oid) != cursor;
             continue;
         }
 
-        if line.starts_with("@@ ") {
-            let header = line.to_string();
-            let (old_start, old_lines, new_start, new_lines) = parse_hunk_header(line);
-            let mut content_lines: Vec<String> = Vec::new();
-            i += 1;
-            while i < lines.len() {
-                let next = lines[i];
-                if next.starts_with("diff --git ") || next.starts_with("@@ ") {
-                    break;
-                }
-                content_lines.push(next.to_string());
-                i += 1;
+        let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+        if let Some(author) = author.as_deref() {
+            let signature = commit.author();
+            let name = String::from_utf8_lossy(signature.name_bytes()).to_lowercase();
+            let email = String::from_utf8_lossy(signature.email_bytes()).to_lowercase();
+            if !name.contains(author) && !email.contains(author) {
+                continue;
+            }
+        }
+        if let Some(message) = message.as_deref() {
+            let text = String::from_utf8_lossy(commit.message_bytes()).to_lowercase();
+            if !text.contains(message) {
+                continue;
+            }
+        }
+        if let Some(path) = path.as_deref() {
+            if !commit_touches_path(&repo, &commit, path)? {
+                continue;
             }
-            let content = content_lines.join("\n");
-            let content_hash = hash_content(&content);
-            let id = format!(
-                "{}:{}:{}:{}:{}",
-                old_start, old_lines, new_start, new_lines, content_hash
-            );
-            let file_header_text = file_header.join("\n");
-            let path = current_path
-                .clone()
-                .unwrap_or_else(|| normalize_repo_path(default_path));
-            hunks.push(DiffHunk {
-                path,
-                kind: kind.clone(),
-                id,
-                header,
-                old_start,
-                old_lines,
-                new_start,
-                new_lines,
-                content,
-                content_hash,
-                file_header: file_header_text,
-            });
-            continue;
         }
 
-        if line.starts_with("index ")
-            || line.starts_with("--- ")
-            || line.starts_with("+++ ")
-            || line.starts_with("new file mode")
-            || line.starts_with("deleted file mode")
-            || line.starts_with("similarity index")
-            || line.starts_with("rename from")
-            || line.starts_with("rename to")
-        {
-            file_header.push(line.to_string());
+        if entries.len() == limit {
+            next_cursor = entries.last().map(|entry: &LogEntry| entry.oid.clone());
+            break;
         }
+        entries.push(log_entry(&commit, &refs));
+    }
 
-        i += 1;
+    if skipping {
+        return Err(STALE_CURSOR.to_string());
     }
 
-    hunks
+    Ok(LogPage {
+        entries,
+        next_cursor,
+    })
 }
 
-pub fn diff_cache_key(
+/// Follows `path` back through renames; the cursor is the oid of the last
+/// returned entry.
+pub fn file_history(
     summary: &RepoSummary,
     path: &str,
-    kind: RepoDiffKind,
-) -> Result<String, String> {
-    const DIFF_CACHE_VERSION: &str = "v2";
-    let normalized_path = normalize_repo_path(path);
-    let old_oid = match kind {
-        RepoDiffKind::Staged => run_git(
-            &summary.path,
-            &["rev-parse", &format!("HEAD:{normalized_path}")],
-            None,
-        )
-            .ok(),
-        RepoDiffKind::Unstaged => {
-            run_git(
-                &summary.path,
-                &["rev-parse", &format!(":{normalized_path}")],
-                None,
-            )
-            .ok()
+    revision: Option<&str>,
+    cursor: Option<&str>,
+    limit: Option<u32>,
+) -> Result<FileHistoryPage, String> {
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+    revwalk
+        .set_sorting(Sort::TOPOLOGICAL | Sort::TIME)
+        .map_err(|e| e.to_string())?;
+    push_revision(&repo, &mut revwalk, revision)?;
+
+    let cursor = page_cursor(&repo, cursor)?;
+    let limit = page_limit(limit);
+    let refs = refs_by_commit(&repo);
+
+    let mut current_path = normalize_repo_path(path);
+    let mut skipping = cursor.is_some();
+    let mut entries = Vec::new();
+    let mut next_cursor = None;
+    for oid in revwalk {
+        let oid = oid.map_err(|e| e.to_string())?;
+        let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+        let Some((change, old_path)) = file_change_in_commit(&repo, &commit, &current_path)? else {
+            continue;
+        };
+
+        if !skipping && entries.len() == limit {
+            next_cursor = entries
+                .last()
+                .map(|entry: &FileHistoryEntry| entry.commit.oid.clone());
+            break;
+        }
+        let path_in_commit = current_path.clone();
+        if let Some(old_path) = old_path.as_ref() {
+            current_path = old_path.clone();
+        }
+        if skipping {
+            skipping = Some(oid) != cursor;
+            continue;
         }
+        entries.push(FileHistoryEntry {
+            commit: log_entry(&commit, &refs),
+            path: path_in_commit,
+            old_path,
+            change,
+        });
+    }
+
+    if skipping {
+        return Err(STALE_CURSOR.to_string());
+    }
+
+    Ok(FileHistoryPage {
+        entries,
+        next_cursor,
+    })
+}
+
+const STALE_CURSOR: &str = "invalid or stale cursor; reload history from the start";
+
+/// Rejects cursors that are not commits in this repository before walking.
+fn page_cursor(repo: &Repository, cursor: Option<&str>) -> Result<Option<Oid>, String> {
+    let Some(cursor) = cursor else {
+        return Ok(None);
     };
+    let oid = Oid::from_str(cursor).map_err(|_| STALE_CURSOR.to_string())?;
+    repo.find_commit(oid).map_err(|_| STALE_CURSOR.to_string())?;
+    Ok(Some(oid))
+}
 
-    let new_oid = match kind {
-        RepoDiffKind::Staged => run_git(
-            &summary.path,
-            &["rev-parse", &format!(":{normalized_path}")],
-            None,
-        )
-        .ok(),
-        RepoDiffKind::Unstaged => run_git(&summary.path, &["hash-object", &normalized_path], None).ok(),
+fn page_limit(limit: Option<u32>) -> usize {
+    limit
+        .map(|value| (value as usize).clamp(1, MAX_LOG_LIMIT))
+        .unwrap_or(DEFAULT_LOG_LIMIT)
+}
+
+/// How `commit` changed `path`, or `None` when it left it untouched. Added
+/// files are checked for renames so history can continue under the old name.
+fn file_change_in_commit(
+    repo: &Repository,
+    commit: &git2::Commit,
+    path: &str,
+) -> Result<Option<(FileChangeKind, Option<String>)>, String> {
+    let tree = commit.tree().map_err(|e| e.to_string())?;
+    let entry_id = |tree: &git2::Tree| tree.get_path(Path::new(path)).ok().map(|e| e.id());
+    let current = entry_id(&tree);
+
+    let mut parent_trees = Vec::new();
+    for parent in commit.parents() {
+        parent_trees.push(parent.tree().map_err(|e| e.to_string())?);
+    }
+    if parent_trees
+        .iter()
+        .any(|parent_tree| entry_id(parent_tree) == current)
+    {
+        return Ok(None);
+    }
+    let parent_tree = parent_trees.first();
+    let previous = parent_tree.and_then(entry_id);
+
+    match (previous, current) {
+        (None, None) => Ok(None),
+        (Some(_), None) => Ok(Some((FileChangeKind::Deleted, None))),
+        (Some(_), Some(_)) => Ok(Some((FileChangeKind::Modified, None))),
+        (None, Some(_)) => {
+            let mut diff = repo
+                .diff_tree_to_tree(parent_tree, Some(&tree), None)
+                .map_err(|e| e.to_string())?;
+            find_renames(&mut diff)?;
+            for delta in diff.deltas() {
+                let new_path = delta
+                    .new_file()
+                    .path()
+                    .map(|value| normalize_repo_path(&value.to_string_lossy()));
+                if new_path.as_deref() != Some(path) {
+                    continue;
+                }
+                let change = file_change_kind(delta.status());
+                return Ok(Some((change, renamed_from(change, delta.old_file().path()))));
+            }
+            Ok(Some((FileChangeKind::Added, None)))
+        }
+    }
+}
+
+fn push_revision(
+    repo: &Repository,
+    revwalk: &mut git2::Revwalk,
+    revision: Option<&str>,
+) -> Result<(), String> {
+    let Some(revision) = revision.map(str::trim).filter(|value| !value.is_empty()) else {
+        return revwalk.push_head().map_err(|e| e.to_string());
     };
 
-    let old_oid = old_oid.map(|value| value.trim().to_string()).unwrap_or_else(|| "none".to_string());
-    let new_oid = new_oid.map(|value| value.trim().to_string()).unwrap_or_else(|| "none".to_string());
-    Ok(format!(
-        "{}:{}:{}:{}:{}",
-        DIFF_CACHE_VERSION, summary.repo_id, normalized_path, old_oid, new_oid
-    ))
+    let spec = repo.revparse(revision).map_err(|e| e.to_string())?;
+    if spec.mode().contains(RevparseMode::SINGLE) {
+        let oid = revspec_commit(spec.from(), revision)?;
+        return revwalk.push(oid).map_err(|e| e.to_string());
+    }
+
+    let from = revspec_commit(spec.from(), revision)?;
+    let to = revspec_commit(spec.to(), revision)?;
+    revwalk.push(to).map_err(|e| e.to_string())?;
+    if spec.mode().contains(RevparseMode::MERGE_BASE) {
+        revwalk.push(from).map_err(|e| e.to_string())?;
+        let base = repo.merge_base(from, to).map_err(|e| e.to_string())?;
+        revwalk.hide(base).map_err(|e| e.to_string())
+    } else {
+        revwalk.hide(from).map_err(|e| e.to_string())
+    }
 }
 
-fn normalize_repo_path(path: &str) -> String {
-    path.replace('\\', "/").trim_start_matches("./").to_string()
+fn lowercase_filter(value: Option<&str>) -> Option<String> {
+    value
+        .map(|text| text.trim().to_lowercase())
+        .filter(|text| !text.is_empty())
 }
 
-fn extract_b_path(line: &str) -> Option<String> {
-    let tokens = parse_diff_header_tokens(line);
-    if tokens.len() >= 2 {
-        let b = tokens[1].trim_start_matches("b/");
-        return Some(normalize_repo_path(b));
+/// Full reference names (plus `HEAD`) keyed by the commit they point at.
+fn refs_by_commit(repo: &Repository) -> std::collections::HashMap<Oid, Vec<String>> {
+    let mut refs: std::collections::HashMap<Oid, Vec<String>> = std::collections::HashMap::new();
+    if let Ok(iter) = repo.references() {
+        for reference in iter.flatten() {
+            if reference.kind() != Some(git2::ReferenceType::Direct) {
+                continue;
+            }
+            let (Some(name), Ok(commit)) = (reference.name(), reference.peel_to_commit()) else {
+                continue;
+            };
+            refs.entry(commit.id()).or_default().push(name.to_string());
+        }
     }
-    None
+    for names in refs.values_mut() {
+        names.sort();
+    }
+    if let Ok(commit) = repo.head().and_then(|head| head.peel_to_commit()) {
+        refs.entry(commit.id()).or_default().insert(0, "HEAD".to_string());
+    }
+    refs
 }
 
-fn parse_diff_header_tokens(line: &str) -> Vec<String> {
-    let Some(rest) = line.strip_prefix("diff --git ") else {
-        return Vec::new();
-    };
+/// A commit touches `path` when it differs from every parent there, which
+/// matches git's default history simplification for merges.
+fn commit_touches_path(
+    repo: &Repository,
+    commit: &git2::Commit,
+    path: &str,
+) -> Result<bool, String> {
+    let tree = commit.tree().map_err(|e| e.to_string())?;
+    if commit.parent_count() == 0 {
+        let mut options = DiffOptions::new();
+        options.pathspec(path);
+        let diff = repo
+            .diff_tree_to_tree(None, Some(&tree), Some(&mut options))
+            .map_err(|e| e.to_string())?;
+        return Ok(diff.deltas().len() > 0);
+    }
 
-    let mut tokens = Vec::new();
-    let mut chars = rest.chars().peekable();
+    for parent in commit.parents() {
+        let parent_tree = parent.tree().map_err(|e| e.to_string())?;
+        let mut options = DiffOptions::new();
+        options.pathspec(path);
+        let diff = repo
+            .diff_tree_to_tree(Some(&parent_tree), Some(&tree), Some(&mut options))
+            .map_err(|e| e.to_string())?;
+        if diff.deltas().len() == 0 {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
 
-    while let Some(ch) = chars.peek() {
-        if ch.is_whitespace() {
-            chars.next();
-            continue;
+fn log_entry(
+    commit: &git2::Commit,
+    refs: &std::collections::HashMap<Oid, Vec<String>>,
+) -> LogEntry {
+    LogEntry {
+        oid: commit.id().to_string(),
+        parents: commit.parent_ids().map(|oid| oid.to_string()).collect(),
+        author: commit_signature(&commit.author()),
+        committer: commit_signature(&commit.committer()),
+        summary: commit.summary().unwrap_or_default().to_string(),
+        refs: refs.get(&commit.id()).cloned().unwrap_or_default(),
+    }
+}
+
+fn commit_signature(signature: &git2::Signature) -> CommitSignature {
+    CommitSignature {
+        name: String::from_utf8_lossy(signature.name_bytes()).to_string(),
+        email: String::from_utf8_lossy(signature.email_bytes()).to_string(),
+        time: signature.when().seconds(),
+        offset_minutes: signature.when().offset_minutes(),
+    }
+}
+
+pub fn blame(
+    summary: &RepoSummary,
+    path: &str,
+    revision: Option<&str>,
+    working_tree: bool,
+) -> Result<BlameResult, String> {
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let normalized_path = normalize_repo_path(path);
+    let mut options = BlameOptions::new();
+    if let Some(revision) = revision.map(str::trim).filter(|value| !value.is_empty()) {
+        options.newest_commit(find_commit(&repo, revision)?.id());
+    }
+    let committed = match repo.blame_file(Path::new(&normalized_path), Some(&mut options)) {
+        Ok(committed) => committed,
+        // New and untracked files have no history: every line is uncommitted.
+        Err(error) if working_tree && error.code() == ErrorCode::NotFound => {
+            let file_path = Path::new(&summary.worktree_path).join(&normalized_path);
+            let contents = std::fs::read(file_path).map_err(|e| e.to_string())?;
+            let line_count = String::from_utf8_lossy(&contents).lines().count() as u32;
+            let ranges = if line_count == 0 {
+                Vec::new()
+            } else {
+                vec![uncommitted_range(1, line_count)]
+            };
+            return Ok(BlameResult {
+                path: normalized_path,
+                ranges,
+            });
         }
+        Err(error) => return Err(error.to_string()),
+    };
+    let blame = if working_tree {
+        let file_path = Path::new(&summary.worktree_path).join(&normalized_path);
+        let contents = std::fs::read(file_path).map_err(|e| e.to_string())?;
+        committed.blame_buffer(&contents).map_err(|e| e.to_string())?
+    } else {
+        committed
+    };
 
-        if *ch == '"' {
-            chars.next();
-            let mut token = String::new();
-            let mut escaped = false;
-            while let Some(c) = chars.next() {
-                if escaped {
-                    token.push(c);
-                    escaped = false;
-                    continue;
-                }
-                if c == '\\' {
-                    escaped = true;
-                    continue;
-                }
-                if c == '"' {
-                    break;
-                }
-                token.push(c);
-            }
-            tokens.push(token);
+    let mut summaries: std::collections::HashMap<Oid, String> = std::collections::HashMap::new();
+    let mut ranges = Vec::new();
+    for hunk in blame.iter() {
+        let oid = hunk.final_commit_id();
+        if oid.is_zero() {
+            ranges.push(uncommitted_range(
+                hunk.final_start_line() as u32,
+                hunk.lines_in_hunk() as u32,
+            ));
             continue;
         }
 
-        let mut token = String::new();
-        while let Some(c) = chars.peek() {
-            if c.is_whitespace() {
-                break;
+        let commit_summary = match summaries.get(&oid) {
+            Some(value) => value.clone(),
+            None => {
+                let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+                let value = commit.summary().unwrap_or_default().to_string();
+                summaries.insert(oid, value.clone());
+                value
+            }
+        };
+        let orig_path = hunk
+            .path()
+            .map(|value| normalize_repo_path(&value.to_string_lossy()))
+            .filter(|value| *value != normalized_path);
+        ranges.push(BlameRange {
+            start_line: hunk.final_start_line() as u32,
+            line_count: hunk.lines_in_hunk() as u32,
+            oid: oid.to_string(),
+            committed: true,
+            author: commit_signature(&hunk.final_signature()),
+            summary: commit_summary,
+            orig_path,
+        });
+    }
+
+    Ok(BlameResult {
+        path: normalized_path,
+        ranges,
+    })
+}
+
+fn uncommitted_range(start_line: u32, line_count: u32) -> BlameRange {
+    BlameRange {
+        start_line,
+        line_count,
+        oid: Oid::zero().to_string(),
+        committed: false,
+        author: CommitSignature {
+            name: "Not Committed Yet".to_string(),
+            email: "not.committed.yet".to_string(),
+            time: crate::store::now_ts() as i64,
+            offset_minutes: 0,
+        },
+        summary: "Not committed yet".to_string(),
+        orig_path: None,
+    }
+}
+
+pub fn stash_list(summary: &RepoSummary) -> Result<Vec<StashEntry>, String> {
+    let mut repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let mut stashes = Vec::new();
+    repo.stash_foreach(|index, message, oid| {
+        stashes.push((index, message.to_string(), *oid));
+        true
+    })
+    .map_err(|e| e.to_string())?;
+
+    Ok(stashes
+        .into_iter()
+        .map(|(index, message, oid)| StashEntry {
+            index: index as u32,
+            oid: oid.to_string(),
+            message,
+            time: repo
+                .find_commit(oid)
+                .map(|commit| commit.time().seconds())
+                .unwrap_or(0),
+        })
+        .collect())
+}
+
+pub fn stash_save(
+    summary: &RepoSummary,
+    message: Option<&str>,
+    include_untracked: bool,
+    keep_index: bool,
+) -> Result<StashEntry, String> {
+    let mut repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let signature = repo.signature().map_err(|e| e.to_string())?;
+    let mut flags = StashFlags::DEFAULT;
+    if include_untracked {
+        flags |= StashFlags::INCLUDE_UNTRACKED;
+    }
+    if keep_index {
+        flags |= StashFlags::KEEP_INDEX;
+    }
+    let message = message.map(str::trim).filter(|value| !value.is_empty());
+    let oid = repo
+        .stash_save2(&signature, message, Some(flags))
+        .map_err(|e| {
+            if e.code() == ErrorCode::NotFound {
+                "No local changes to stash.".to_string()
+            } else {
+                e.to_string()
+            }
+        })?
+        .to_string();
+
+    stash_list(summary)?
+        .into_iter()
+        .find(|entry| entry.oid == oid)
+        .ok_or_else(|| "stash entry not found".to_string())
+}
+
+pub fn stash_apply(summary: &RepoSummary, index: u32, oid: &str) -> Result<(), RepoError> {
+    run_stash_apply(summary, "apply", index, oid)
+}
+
+/// Like `stash_apply`, but drops the entry once it applied cleanly.
+pub fn stash_pop(summary: &RepoSummary, index: u32, oid: &str) -> Result<(), RepoError> {
+    run_stash_apply(summary, "pop", index, oid)
+}
+
+fn run_stash_apply(
+    summary: &RepoSummary,
+    command: &str,
+    index: u32,
+    oid: &str,
+) -> Result<(), RepoError> {
+    let reference = stash_reference(summary, index, oid)
+        .map_err(|message| RepoError::GitError { message })?;
+    let Err(message) = run_git(&summary.path, &["stash", command, &reference], None) else {
+        return Ok(());
+    };
+    let conflicts = conflicted_paths_for(summary)?;
+    if conflicts.is_empty() {
+        return Err(RepoError::GitError { message });
+    }
+    Err(RepoError::MergeConflict {
+        message: format!("Applying {reference} produced conflicts."),
+        paths: conflicts,
+    })
+}
+
+pub fn stash_drop(summary: &RepoSummary, index: u32, oid: &str) -> Result<(), String> {
+    stash_reference(summary, index, oid)?;
+    let mut repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    repo.stash_drop(index as usize).map_err(|e| e.to_string())
+}
+
+/// Returns `stash@{index}` only while it still points at `oid`.
+fn stash_reference(summary: &RepoSummary, index: u32, oid: &str) -> Result<String, String> {
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let reference = format!("stash@{{{index}}}");
+    let current = repo
+        .revparse_single(&reference)
+        .map(|object| object.id().to_string())
+        .ok();
+    if current.as_deref() != Some(oid) {
+        return Err(format!(
+            "{reference} no longer matches the selected stash; refresh the stash list."
+        ));
+    }
+    Ok(reference)
+}
+
+/// Tracked changes come from the stash commit itself; untracked files, when
+/// stashed, live in its third parent.
+pub fn stash_show(summary: &RepoSummary, index: u32) -> Result<StashDetail, String> {
+    let entry = stash_list(summary)?
+        .into_iter()
+        .find(|entry| entry.index == index)
+        .ok_or_else(|| "unknown stash index".to_string())?;
+    let repo = Repository::open(&summary.path).map_err(|e| e.to_string())?;
+    let commit = find_commit(&repo, &entry.oid)?;
+    let mut files = commit_file_changes(&repo, &commit)?;
+    if let Ok(untracked) = commit.parent(2) {
+        files.extend(commit_file_changes(&repo, &untracked)?);
+    }
+    Ok(StashDetail { entry, files })
+}
+
+pub fn list_worktrees(repo_root: &str) -> Result<WorktreeList, String> {
+    let output = run_git(repo_root, &["worktree", "list", "--porcelain"], None)?;
+    let mut worktrees = Vec::new();
+    let mut current_path = None;
+    let mut current_head = None;
+    let mut current_branch = None;
+
+    for line in output.lines() {
+        if line.starts_with("worktree ") {
+            if let Some(path) = current_path.take() {
+                worktrees.push(WorktreeInfo {
+                    path,
+                    head: current_head.take().unwrap_or_else(|| "unknown".to_string()),
+                    branch: current_branch.take().unwrap_or_else(|| "DETACHED".to_string()),
+                });
+            }
+            current_path = Some(line.trim_start_matches("worktree ").trim().to_string());
+        } else if line.starts_with("HEAD ") {
+            current_head = Some(line.trim_start_matches("HEAD ").trim().to_string());
+        } else if line.starts_with("branch ") {
+            let branch = line.trim_start_matches("branch ").trim().to_string();
+            current_branch = Some(branch.trim_start_matches("refs/heads/").to_string());
+        }
+    }
+
+    if let Some(path) = current_path.take() {
+        worktrees.push(WorktreeInfo {
+            path,
+            head: current_head.take().unwrap_or_else(|| "unknown".to_string()),
+            branch: current_branch.take().unwrap_or_else(|| "DETACHED".to_string()),
+        });
+    }
+
+    Ok(WorktreeList {
+        repo_root: repo_root.to_string(),
+        worktrees,
+    })
+}
+
+pub fn add_worktree(
+    repo_root: &str,
+    path: &str,
+    branch_name: &str,
+    new_branch: bool,
+) -> Result<WorktreeResult, String> {
+    let mut args = vec!["worktree", "add"];
+    if new_branch {
+        args.push("-b");
+        args.push(branch_name);
+        args.push(path);
+        args.push("HEAD");
+    } else {
+        args.push(path);
+        args.push(branch_name);
+    }
+    run_git(repo_root, &args, None)?;
+    Ok(WorktreeResult {
+        ok: true,
+        message: "worktree added".to_string(),
+    })
+}
+
+pub fn remove_worktree(repo_root: &str, path: &str) -> Result<WorktreeResult, String> {
+    run_git(repo_root, &["worktree", "remove", path], None)?;
+    Ok(WorktreeResult {
+        ok: true,
+        message: "worktree removed".to_string(),
+    })
+}
+
+pub fn prune_worktrees(repo_root: &str) -> Result<WorktreeResult, String> {
+    run_git(repo_root, &["worktree", "prune"], None)?;
+    Ok(WorktreeResult {
+        ok: true,
+        message: "worktrees pruned".to_string(),
+    })
+}
+
+pub fn repo_id_for_path(path: &str) -> RepoId {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+fn repo_head(repo: &Repository) -> Result<RepoHead, String> {
+    match repo.head() {
+        Ok(head) => {
+            let detached = !head.is_branch();
+            let branch_name = if detached {
+                None
+            } else {
+                head.shorthand().map(|name| name.to_string())
+            };
+            let oid_short = head
+                .target()
+                .map(|oid| oid.to_string())
+                .map(|oid| oid.chars().take(7).collect::<String>())
+                .unwrap_or_else(|| "unknown".to_string());
+
+            Ok(RepoHead {
+                branch_name,
+                oid_short,
+                detached,
+            })
+        }
+        // An unborn branch still names the branch the first commit will create.
+        Err(_) => Ok(RepoHead {
+            branch_name: repo
+                .find_reference("HEAD")
+                .ok()
+                .and_then(|head| head.symbolic_target().map(|name| name.to_string()))
+                .map(|name| name.trim_start_matches("refs/heads/").to_string()),
+            oid_short: "—".to_string(),
+            detached: false,
+        }),
+    }
+}
+
+fn is_index_change(status: Status) -> bool {
+    status.intersects(
+        Status::INDEX_NEW
+            | Status::INDEX_MODIFIED
+            | Status::INDEX_DELETED
+            | Status::INDEX_RENAMED
+            | Status::INDEX_TYPECHANGE,
+    )
+}
+
+fn is_workdir_change(status: Status) -> bool {
+    status.intersects(
+        Status::WT_MODIFIED
+            | Status::WT_DELETED
+            | Status::WT_RENAMED
+            | Status::WT_TYPECHANGE,
+    )
+}
+
+fn extract_paths(entry: &git2::StatusEntry) -> (Option<String>, Option<String>) {
+    let mut path: Option<PathBuf> = None;
+    let mut old_path: Option<PathBuf> = None;
+
+    if let Some(delta) = entry.head_to_index() {
+        if let Some(new_file) = delta.new_file().path() {
+            path = Some(new_file.to_path_buf());
+        }
+        if let Some(old_file) = delta.old_file().path() {
+            old_path = Some(old_file.to_path_buf());
+        }
+    }
+
+    if path.is_none() {
+        if let Some(delta) = entry.index_to_workdir() {
+            if let Some(new_file) = delta.new_file().path() {
+                path = Some(new_file.to_path_buf());
+            }
+            if let Some(old_file) = delta.old_file().path() {
+                old_path = Some(old_file.to_path_buf());
+            }
+        }
+    }
+
+    (
+        path.map(|p| p.to_string_lossy().to_string()),
+        old_path.map(|p| p.to_string_lossy().to_string()),
+    )
+}
+
+fn parse_diff_hunks(diff_text: &str, default_path: &str, kind: RepoDiffKind) -> Vec<DiffHunk> {
+    let mut hunks = Vec::new();
+    let lines: Vec<&str> = diff_text.lines().collect();
+    let mut i = 0;
+    let mut file_header: Vec<String> = Vec::new();
+    let mut current_path: Option<String> = None;
+
+    while i < lines.len() {
+        let line = lines[i];
+        if line.starts_with("diff --git ") {
+            file_header.clear();
+            file_header.push(line.to_string());
+            current_path = extract_b_path(line);
+            i += 1;
+            continue;
+        }
+
+        if line.starts_with("@@ ") {
+            let header = line.to_string();
+            let (old_start, old_lines, new_start, new_lines) = parse_hunk_header(line);
+            let mut content_lines: Vec<String> = Vec::new();
+            i += 1;
+            while i < lines.len() {
+                let next = lines[i];
+                if next.starts_with("diff --git ") || next.starts_with("@@ ") {
+                    break;
+                }
+                content_lines.push(next.to_string());
+                i += 1;
+            }
+            let content = content_lines.join("\n");
+            let content_hash = hash_content(&content);
+            let id = format!(
+                "{}:{}:{}:{}:{}",
+                old_start, old_lines, new_start, new_lines, content_hash
+            );
+            let file_header_text = file_header.join("\n");
+            let path = current_path
+                .clone()
+                .unwrap_or_else(|| normalize_repo_path(default_path));
+            hunks.push(DiffHunk {
+                path,
+                kind: kind.clone(),
+                id,
+                header,
+                old_start,
+                old_lines,
+                new_start,
+                new_lines,
+                content,
+                content_hash,
+                file_header: file_header_text,
+            });
+            continue;
+        }
+
+        if line.starts_with("index ")
+            || line.starts_with("--- ")
+            || line.starts_with("+++ ")
+            || line.starts_with("new file mode")
+            || line.starts_with("deleted file mode")
+            || line.starts_with("similarity index")
+            || line.starts_with("rename from")
+            || line.starts_with("rename to")
+        {
+            file_header.push(line.to_string());
+        }
+
+        i += 1;
+    }
+
+    hunks
+}
+
+/// `tree_oids` is the result of `tree_diff_oids` for `kind`, passed in so
+/// the key matches the commits the caller goes on to diff.
+pub fn diff_cache_key(
+    summary: &RepoSummary,
+    path: &str,
+    kind: RepoDiffKind,
+    tree_oids: Option<&(Option<String>, String)>,
+) -> Result<String, String> {
+    const DIFF_CACHE_VERSION: &str = "v2";
+    let normalized_path = normalize_repo_path(path);
+    if let Some((old_oid, new_oid)) = tree_oids {
+        return Ok(format!(
+            "{}:{}:{}:{}:{}",
+            DIFF_CACHE_VERSION,
+            summary.repo_id,
+            normalized_path,
+            old_oid.as_deref().unwrap_or("none"),
+            new_oid
+        ));
+    }
+    let old_oid = match &kind {
+        RepoDiffKind::Staged => run_git(
+            &summary.path,
+            &["rev-parse", &format!("HEAD:{normalized_path}")],
+            None,
+        )
+            .ok(),
+        RepoDiffKind::Unstaged => {
+            run_git(
+                &summary.path,
+                &["rev-parse", &format!(":{normalized_path}")],
+                None,
+            )
+            .ok()
+        }
+        RepoDiffKind::Commit { .. } | RepoDiffKind::Range { .. } => None,
+    };
+
+    let new_oid = match &kind {
+        RepoDiffKind::Staged => run_git(
+            &summary.path,
+            &["rev-parse", &format!(":{normalized_path}")],
+            None,
+        )
+        .ok(),
+        RepoDiffKind::Unstaged => run_git(&summary.path, &["hash-object", &normalized_path], None).ok(),
+        RepoDiffKind::Commit { .. } | RepoDiffKind::Range { .. } => None,
+    };
+
+    let old_oid = old_oid.map(|value| value.trim().to_string()).unwrap_or_else(|| "none".to_string());
+    let new_oid = new_oid.map(|value| value.trim().to_string()).unwrap_or_else(|| "none".to_string());
+    Ok(format!(
+        "{}:{}:{}:{}:{}",
+        DIFF_CACHE_VERSION, summary.repo_id, normalized_path, old_oid, new_oid
+    ))
+}
+
+pub fn normalize_repo_path(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches("./").to_string()
+}
+
+fn extract_b_path(line: &str) -> Option<String> {
+    let tokens = parse_diff_header_tokens(line);
+    if tokens.len() >= 2 {
+        let b = tokens[1].trim_start_matches("b/");
+        return Some(normalize_repo_path(b));
+    }
+    None
+}
+
+fn parse_diff_header_tokens(line: &str) -> Vec<String> {
+    let Some(rest) = line.strip_prefix("diff --git ") else {
+        return Vec::new();
+    };
+
+    let mut tokens = Vec::new();
+    let mut chars = rest.chars().peekable();
+
+    while let Some(ch) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        if *ch == '"' {
+            chars.next();
+            let mut token = String::new();
+            let mut escaped = false;
+            while let Some(c) = chars.next() {
+                if escaped {
+                    token.push(c);
+                    escaped = false;
+                    continue;
+                }
+                if c == '\\' {
+                    escaped = true;
+                    continue;
+                }
+                if c == '"' {
+                    break;
+                }
+                token.push(c);
+            }
+            tokens.push(token);
+            continue;
+        }
+
+        let mut token = String::new();
+        while let Some(c) = chars.peek() {
+            if c.is_whitespace() {
+                break;
+            }
+            token.push(*c);
+            chars.next();
+        }
+        if !token.is_empty() {
+            tokens.push(token);
+        }
+    }
+
+    tokens
+}
+
+fn parse_hunk_header(line: &str) -> (u32, u32, u32, u32) {
+    let header = line.trim_start_matches("@@ ").trim_end_matches("@@");
+    let mut parts = header.split(" @@").next().unwrap_or("").split_whitespace();
+    let old_part = parts.next().unwrap_or("-0");
+    let new_part = parts.next().unwrap_or("+0");
+    let (old_start, old_lines) = parse_range(old_part.trim_start_matches('-'));
+    let (new_start, new_lines) = parse_range(new_part.trim_start_matches('+'));
+    (old_start, old_lines, new_start, new_lines)
+}
+
+fn parse_range(text: &str) -> (u32, u32) {
+    let mut parts = text.split(',');
+    let start = parts
+        .next()
+        .and_then(|value| value.parse::<u32>().ok())
+        .unwrap_or(0);
+    let lines = parts
+        .next()
+        .and_then(|value| value.parse::<u32>().ok())
+        .unwrap_or(1);
+    (start, lines)
+}
+
+fn hash_content(text: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.as_bytes() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        blame, checkout_branch, cherry_pick, commit_changelist, commit_changelist_with_hunks,
+        commit_detail, conflict_versions, create_branch, create_tag, delete_branch,
+        delete_remote_branch, delete_tag, diff_hunks_for_path, file_history, list_branches,
+        list_tags, log, operation_abort, operation_continue, operation_skip, page_limit,
+        parse_diff_hunks, pull, push, push_tags, range_diff, range_diff_cache_key, rename_branch,
+        reset, resolve_conflict, resolve_range, revert, revert_changelist_changes, run_git,
+        select_hunk_lines, set_upstream, stage_path, stash_apply, stash_drop, stash_list,
+        stash_pop, stash_save, stash_show, status, track_path, unstage_path, MAX_LOG_LIMIT,
+        RepoDiffKind,
+    };
+    use crate::model::{
+        CheckoutMode, CheckoutTarget, CheckoutTargetKind, CommitAuthor, CommitOptions,
+        CommitTrailer, ConflictResolution, FileChangeKind, HunkAssignment, HunkLineRange,
+        PullStrategy, PushRejectReason, RepoError, RepoLogRequest, RepoOperation, RepoSummary,
+        ResetMode, SignatureStatus, StatusFile, StatusKind,
+    };
+    use git2::{Repository, Signature};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn hunk_id_changes_with_content() {
+        let diff = "\
+diff --git a/foo.txt b/foo.txt\n\
+index 1111111..2222222 100644\n\
+--- a/foo.txt\n\
++++ b/foo.txt\n\
+@@ -1,2 +1,2 @@\n\
+-hello\n\
++hello world\n\
+ line2\n";
+
+        let hunks = parse_diff_hunks(diff, "foo.txt", RepoDiffKind::Unstaged);
+        assert_eq!(hunks.len(), 1);
+        let first_id = hunks[0].id.clone();
+
+        let diff_changed = "\
+diff --git a/foo.txt b/foo.txt\n\
+index 1111111..2222222 100644\n\
+--- a/foo.txt\n\
++++ b/foo.txt\n\
+@@ -1,2 +1,2 @@\n\
+-hello\n\
++hello brave world\n\
+ line2\n";
+        let hunks_changed = parse_diff_hunks(diff_changed, "foo.txt", RepoDiffKind::Unstaged);
+        assert_eq!(hunks_changed.len(), 1);
+        assert_ne!(first_id, hunks_changed[0].id);
+    }
+
+    #[test]
+    fn select_hunk_lines_keeps_only_selected_changes() {
+        let content = " a\n-b\n+B\n c\n+d";
+        let ranges = [HunkLineRange { start: 2, end: 2 }];
+        let partial = select_hunk_lines(content, Some(&ranges), false).expect("partial hunk");
+        assert_eq!(partial.content, " a\n b\n+B\n c\n");
+        assert_eq!((partial.old_lines, partial.new_lines), (3, 4));
+
+        let context_only = [HunkLineRange { start: 0, end: 0 }];
+        assert!(select_hunk_lines(content, Some(&context_only), false).is_none());
+    }
+
+    fn temp_repo_path() -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        std::env::temp_dir().join(format!("gitpanel-git-test-{nanos}"))
+    }
+
+    fn repo_summary(path: &Path) -> RepoSummary {
+        let path_text = path.to_string_lossy().to_string();
+        RepoSummary {
+            repo_id: "test-repo".to_string(),
+            path: path_text.clone(),
+            name: "test".to_string(),
+            repo_root: path_text.clone(),
+            worktree_path: path_text,
+            is_valid: true,
+        }
+    }
+
+    fn init_repo_with_commit() -> (RepoSummary, PathBuf) {
+        let path = temp_repo_path();
+        fs::create_dir_all(&path).expect("create repo root");
+        let repo = Repository::init(&path).expect("init repo");
+
+        fs::write(path.join("tracked.txt"), "line-1\n").expect("write tracked");
+
+        let mut index = repo.index().expect("index");
+        index
+            .add_path(Path::new("tracked.txt"))
+            .expect("stage tracked");
+        index.write().expect("write index");
+
+        let tree_id = index.write_tree().expect("write tree");
+        let tree = repo.find_tree(tree_id).expect("find tree");
+        let sig = Signature::now("gitpanel-test", "test@example.com").expect("signature");
+        repo.commit(Some("HEAD"), &sig, &sig, "initial commit", &tree, &[])
+            .expect("commit");
+
+        (repo_summary(&path), path)
+    }
+
+    fn commit_file(path: &Path, file: &str, content: &str, message: &str) {
+        let repo = Repository::open(path).expect("open repo");
+        fs::write(path.join(file), content).expect("write file");
+        let mut index = repo.index().expect("index");
+        index.add_path(Path::new(file)).expect("stage file");
+        index.write().expect("write index");
+        let tree = repo
+            .find_tree(index.write_tree().expect("write tree"))
+            .expect("find tree");
+        let sig = Signature::now("gitpanel-test", "test@example.com").expect("signature");
+        let parent = repo.head().expect("head").peel_to_commit().expect("parent");
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &[&parent])
+            .expect("commit");
+    }
+
+    fn set_identity(path: &Path) {
+        let repo = Repository::open(path).expect("open repo");
+        let mut config = repo.config().expect("config");
+        config.set_str("user.name", "gitpanel-test").expect("user name");
+        config
+            .set_str("user.email", "test@example.com")
+            .expect("user email");
+    }
+
+    fn unstaged_file(path: &str) -> StatusFile {
+        StatusFile {
+            path: path.to_string(),
+            status: StatusKind::Unstaged,
+            old_path: None,
+            changelist_id: None,
+            changelist_name: None,
+            changelist_partial: None,
+        }
+    }
+
+    fn init_repo_with_conflict() -> (RepoSummary, PathBuf) {
+        let (summary, path) = init_repo_with_commit();
+        set_identity(&path);
+        run_git(&summary.path, &["checkout", "-q", "-b", "other"], None).expect("branch");
+        commit_file(&path, "tracked.txt", "line-theirs\n", "theirs");
+        run_git(&summary.path, &["checkout", "-q", "-"], None).expect("checkout back");
+        commit_file(&path, "tracked.txt", "line-ours\n", "ours");
+        assert!(run_git(&summary.path, &["merge", "other"], None).is_err());
+        (summary, path)
+    }
+
+    #[test]
+    fn unstage_newly_added_file_removes_index_entry() {
+        let (summary, path) = init_repo_with_commit();
+        fs::write(path.join("new.txt"), "new-line\n").expect("write new");
+
+        stage_path(&summary, "new.txt").expect("stage new");
+
+        {
+            let repo = Repository::open(&summary.path).expect("open repo");
+            let index = repo.index().expect("open index");
+            assert!(index.get_path(Path::new("new.txt"), 0).is_some());
+        }
+
+        unstage_path(&summary, "new.txt").expect("unstage new");
+
+        {
+            let repo = Repository::open(&summary.path).expect("open repo");
+            let index = repo.index().expect("open index");
+            assert!(index.get_path(Path::new("new.txt"), 0).is_none());
+        }
+
+        let repo_status = status(&summary).expect("status");
+        let entry = repo_status
+            .files
+            .iter()
+            .find(|file| file.path == "new.txt")
+            .expect("new file in status");
+        assert!(matches!(entry.status, StatusKind::Untracked));
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn unstage_tracked_file_keeps_worktree_change() {
+        let (summary, path) = init_repo_with_commit();
+        fs::write(path.join("tracked.txt"), "line-2\n").expect("mutate tracked");
+
+        stage_path(&summary, "tracked.txt").expect("stage tracked");
+        unstage_path(&summary, "tracked.txt").expect("unstage tracked");
+
+        {
+            let repo = Repository::open(&summary.path).expect("open repo");
+            let index = repo.index().expect("open index");
+            let index_entry = index
+                .get_path(Path::new("tracked.txt"), 0)
+                .expect("tracked in index");
+            let head_commit = repo
+                .head()
+                .expect("head")
+                .peel_to_commit()
+                .expect("head commit");
+            let head_tree = head_commit.tree().expect("head tree");
+            let head_entry = head_tree
+                .get_path(Path::new("tracked.txt"))
+                .expect("tracked in head");
+            assert_eq!(index_entry.id, head_entry.id());
+        }
+
+        let repo_status = status(&summary).expect("status");
+        assert_eq!(repo_status.counts.staged, 0);
+        assert!(repo_status
+            .files
+            .iter()
+            .any(|file| file.path == "tracked.txt"));
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn track_new_file_marks_it_as_unstaged() {
+        let (summary, path) = init_repo_with_commit();
+        fs::write(path.join("intent.txt"), "intent\n").expect("write intent");
+
+        track_path(&summary, "intent.txt").expect("track file");
+
+        let repo_status = status(&summary).expect("status");
+        let entry = repo_status
+            .files
+            .iter()
+            .find(|file| file.path == "intent.txt")
+            .expect("intent file in status");
+        assert!(matches!(entry.status, StatusKind::Unstaged));
+        assert_eq!(repo_status.counts.staged, 0);
+        assert_eq!(repo_status.counts.untracked, 0);
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn list_branches_reports_ahead_behind_against_upstream() {
+        let (summary, path) = init_repo_with_commit();
+        let repo = Repository::open(&path).expect("open repo");
+        let head = repo.head().expect("head").peel_to_commit().expect("head commit");
+        let mut feature = repo.branch("feature", &head, false).expect("create feature");
+        let current = repo.head().expect("head").shorthand().expect("name").to_string();
+        feature.set_upstream(Some(&current)).expect("set upstream");
+
+        fs::write(path.join("tracked.txt"), "line-2\n").expect("mutate tracked");
+        let mut index = repo.index().expect("index");
+        index.add_path(Path::new("tracked.txt")).expect("stage");
+        let tree = repo.find_tree(index.write_tree().expect("tree")).expect("find tree");
+        let sig = Signature::now("gitpanel-test", "test@example.com").expect("signature");
+        repo.commit(Some("HEAD"), &sig, &sig, "second", &tree, &[&head])
+            .expect("commit");
+
+        let branches = list_branches(&summary).expect("branches");
+        let counts = branches
+            .ahead_behind
+            .as_ref()
+            .and_then(|map| map.get("feature"))
+            .expect("feature counts");
+        assert_eq!((counts.ahead, counts.behind), (0, 1));
+        assert_eq!(branches.upstreams.get("feature"), Some(&current));
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn push_to_bare_remote_sets_upstream_and_reports_rejections() {
+        let (summary, path) = init_repo_with_commit();
+        let remote_path = temp_repo_path();
+        Repository::init_bare(&remote_path).expect("init bare");
+        let repo = Repository::open(&path).expect("open repo");
+        repo.remote("origin", &remote_path.to_string_lossy())
+            .expect("add remote");
+        let branch = repo.head().expect("head").shorthand().expect("name").to_string();
+
+        let result = push(&summary, None, None, true, None).expect("push");
+        assert_eq!(result.branch, branch);
+        assert_eq!(result.upstream, Some(format!("origin/{branch}")));
+        let bare = Repository::open_bare(&remote_path).expect("open bare");
+        assert_eq!(
+            bare.refname_to_id(&format!("refs/heads/{branch}")).ok(),
+            repo.head().expect("head").target()
+        );
+
+        let mirror_path = temp_repo_path();
+        Repository::init_bare(&mirror_path).expect("init mirror");
+        repo.remote("mirror", &mirror_path.to_string_lossy())
+            .expect("add mirror");
+        repo.config()
+            .expect("config")
+            .set_str(&format!("branch.{branch}.remote"), "mirror")
+            .expect("set branch remote");
+        let result = push(&summary, None, None, false, None).expect("push to branch remote");
+        assert_eq!(result.remote, "mirror");
+        let mirror = Repository::open_bare(&mirror_path).expect("open mirror");
+        assert!(mirror.refname_to_id(&format!("refs/heads/{branch}")).is_ok());
+
+        let tree = repo
+            .head()
+            .expect("head")
+            .peel_to_tree()
+            .expect("head tree");
+        let sig = Signature::now("gitpanel-test", "test@example.com").expect("signature");
+        let diverged = repo
+            .commit(None, &sig, &sig, "unrelated", &tree, &[])
+            .expect("commit");
+        repo.reference(&format!("refs/heads/{branch}"), diverged, true, "diverge")
+            .expect("move branch");
+
+        match push(&summary, Some("origin"), Some(&branch), false, None) {
+            Err(RepoError::PushRejected { rejections, .. }) => {
+                assert_eq!(rejections.len(), 1);
+                assert_eq!(rejections[0].reason, PushRejectReason::NonFastForward);
+            }
+            other => panic!("expected push rejection, got {other:?}"),
+        }
+
+        let _ = fs::remove_dir_all(path);
+        let _ = fs::remove_dir_all(remote_path);
+        let _ = fs::remove_dir_all(mirror_path);
+    }
+
+    #[test]
+    fn push_updates_the_configured_upstream_branch() {
+        let (summary, path) = init_repo_with_commit();
+        let remote_path = temp_repo_path();
+        Repository::init_bare(&remote_path).expect("init bare");
+        let repo = Repository::open(&path).expect("open repo");
+        repo.remote("origin", &remote_path.to_string_lossy())
+            .expect("add remote");
+        let head = repo.head().expect("head").peel_to_commit().expect("head commit");
+        repo.branch("feature", &head, false).expect("create feature");
+        let mut config = repo.config().expect("config");
+        config
+            .set_str("branch.feature.remote", "origin")
+            .expect("set branch remote");
+        config
+            .set_str("branch.feature.merge", "refs/heads/team/feature")
+            .expect("set branch merge");
+
+        let result = push(&summary, None, Some("feature"), true, None).expect("push");
+        assert_eq!(result.upstream.as_deref(), Some("origin/team/feature"));
+        let bare = Repository::open_bare(&remote_path).expect("open bare");
+        assert_eq!(bare.refname_to_id("refs/heads/team/feature").ok(), Some(head.id()));
+        assert!(bare.refname_to_id("refs/heads/feature").is_err());
+
+        let _ = fs::remove_dir_all(path);
+        let _ = fs::remove_dir_all(remote_path);
+    }
+
+    #[test]
+    fn pull_fast_forwards_and_reports_conflicts() {
+        let (summary, path) = init_repo_with_commit();
+        let remote_path = temp_repo_path();
+        Repository::init_bare(&remote_path).expect("init bare");
+        Repository::open(&path)
+            .expect("open repo")
+            .remote("origin", &remote_path.to_string_lossy())
+            .expect("add remote");
+        push(&summary, None, None, true, None).expect("initial push");
+
+        let clone_path = temp_repo_path();
+        Repository::clone(&remote_path.to_string_lossy(), &clone_path).expect("clone");
+        set_identity(&clone_path);
+        let clone_summary = repo_summary(&clone_path);
+
+        commit_file(&path, "tracked.txt", "line-2\n", "second");
+        push(&summary, None, None, false, None).expect("push second");
+        let result = pull(&clone_summary, PullStrategy::FastForward, None).expect("pull");
+        assert_eq!(result.updated_files, vec!["tracked.txt".to_string()]);
+        assert_eq!(
+            fs::read_to_string(clone_path.join("tracked.txt")).expect("read tracked"),
+            "line-2\n"
+        );
+
+        commit_file(&path, "tracked.txt", "line-upstream\n", "upstream");
+        push(&summary, None, None, false, None).expect("push upstream");
+        commit_file(&clone_path, "tracked.txt", "line-local\n", "local");
+        match pull(&clone_summary, PullStrategy::Merge, None) {
+            Err(RepoError::MergeConflict { paths, .. }) => {
+                assert_eq!(paths, vec!["tracked.txt".to_string()]);
+            }
+            other => panic!("expected merge conflict, got {other:?}"),
+        }
+
+        let _ = fs::remove_dir_all(path);
+        let _ = fs::remove_dir_all(remote_path);
+        let _ = fs::remove_dir_all(clone_path);
+    }
+
+    #[test]
+    fn commit_with_line_ranges_commits_selected_lines_only() {
+        let (summary, path) = init_repo_with_commit();
+        let repo = Repository::open(&path).expect("open repo");
+        let mut config = repo.config().expect("config");
+        config.set_str("user.name", "gitpanel-test").expect("user name");
+        config
+            .set_str("user.email", "test@example.com")
+            .expect("user email");
+        fs::write(path.join("tracked.txt"), "line-1\nline-2\nline-3\n").expect("mutate tracked");
+
+        let hunk = diff_hunks_for_path(&summary, "tracked.txt", RepoDiffKind::Unstaged)
+            .expect("hunks")
+            .remove(0);
+        let assignment = HunkAssignment {
+            id: hunk.id.clone(),
+            header: hunk.header.clone(),
+            old_start: hunk.old_start,
+            old_lines: hunk.old_lines,
+            new_start: hunk.new_start,
+            new_lines: hunk.new_lines,
+            content_hash: hunk.content_hash.clone(),
+            kind: RepoDiffKind::Unstaged,
+            line_ranges: Some(vec![HunkLineRange { start: 1, end: 1 }]),
+            content: None,
+        };
+
+        commit_changelist_with_hunks(
+            &summary,
+            &[],
+            &[("tracked.txt".to_string(), vec![assignment])],
+            "partial",
+            &CommitOptions::default(),
+        )
+        .expect("commit selected lines");
+
+        let head_tree = repo
+            .head()
+            .expect("head")
+            .peel_to_tree()
+            .expect("head tree");
+        let entry = head_tree
+            .get_path(Path::new("tracked.txt"))
+            .expect("tracked in head");
+        let blob = repo.find_blob(entry.id()).expect("blob");
+        assert_eq!(blob.content(), b"line-1\nline-2\n");
+        assert_eq!(
+            fs::read_to_string(path.join("tracked.txt")).expect("read tracked"),
+            "line-1\nline-2\nline-3\n"
+        );
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn revert_with_line_ranges_keeps_unselected_lines() {
+        let (summary, path) = init_repo_with_commit();
+        fs::write(path.join("tracked.txt"), "line-1\nline-2\nline-3\n").expect("mutate tracked");
+        let hunk = diff_hunks_for_path(&summary, "tracked.txt", RepoDiffKind::Unstaged)
+            .expect("hunks")
+            .remove(0);
+        let assignment = HunkAssignment {
+            id: hunk.id.clone(),
+            header: hunk.header.clone(),
+            old_start: hunk.old_start,
+            old_lines: hunk.old_lines,
+            new_start: hunk.new_start,
+            new_lines: hunk.new_lines,
+            content_hash: hunk.content_hash.clone(),
+            kind: RepoDiffKind::Unstaged,
+            line_ranges: Some(vec![HunkLineRange { start: 2, end: 2 }]),
+            content: None,
+        };
+
+        revert_changelist_changes(&summary, &[], &[("tracked.txt".to_string(), vec![assignment])])
+            .expect("revert selected lines");
+        assert_eq!(
+            fs::read_to_string(path.join("tracked.txt")).expect("read tracked"),
+            "line-1\nline-2\n"
+        );
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn log_paginates_and_filters_history() {
+        let (summary, path) = init_repo_with_commit();
+        commit_file(&path, "feature.txt", "feature\n", "feat: add feature");
+        commit_file(&path, "tracked.txt", "line-1\nline-2\n", "fix: extend tracked");
+        let request = |limit: Option<u32>, cursor: Option<String>| RepoLogRequest {
+            repo_id: summary.repo_id.clone(),
+            revision: None,
+            cursor,
+            limit,
+            path: None,
+            author: None,
+            message: None,
+        };
+
+        let first = log(&summary, &request(Some(2), None)).expect("first page");
+        assert_eq!(first.entries.len(), 2);
+        assert_eq!(first.entries[0].summary, "fix: extend tracked");
+        assert_eq!(first.entries[0].refs[0], "HEAD");
+        assert_eq!(first.entries[0].parents, vec![first.entries[1].oid.clone()]);
+        let cursor = first.next_cursor.clone().expect("next cursor");
+
+        let second = log(&summary, &request(Some(2), Some(cursor))).expect("second page");
+        assert_eq!(second.entries.len(), 1);
+        assert_eq!(second.entries[0].summary, "initial commit");
+        assert!(second.next_cursor.is_none());
+
+        let mut by_path = request(None, None);
+        by_path.path = Some("tracked.txt".to_string());
+        let by_path = log(&summary, &by_path).expect("path filter");
+        let summaries: Vec<&str> = by_path.entries.iter().map(|e| e.summary.as_str()).collect();
+        assert_eq!(summaries, vec!["fix: extend tracked", "initial commit"]);
+
+        let mut by_message = request(None, None);
+        by_message.message = Some("FEAT".to_string());
+        by_message.author = Some("gitpanel".to_string());
+        let by_message = log(&summary, &by_message).expect("message filter");
+        assert_eq!(by_message.entries.len(), 1);
+        assert_eq!(by_message.entries[0].author.email, "test@example.com");
+
+        let mut range = request(None, None);
+        range.revision = Some("HEAD~2..HEAD~1".to_string());
+        let range = log(&summary, &range).expect("range");
+        assert_eq!(range.entries.len(), 1);
+        assert_eq!(range.entries[0].summary, "feat: add feature");
+
+        let stale = request(Some(2), Some("1".repeat(40)));
+        assert!(log(&summary, &stale).unwrap_err().contains("stale cursor"));
+        let mut unreachable = request(Some(2), Some(first.entries[0].oid.clone()));
+        unreachable.revision = Some("HEAD~1".to_string());
+        assert!(log(&summary, &unreachable).unwrap_err().contains("stale cursor"));
+        assert_eq!(page_limit(Some(u32::MAX)), MAX_LOG_LIMIT);
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn commit_detail_reports_renames_and_hunks() {
+        let (summary, path) = init_repo_with_commit();
+        commit_file(&path, "notes.txt", "alpha\nbeta\ngamma\ndelta\n", "add notes");
+        {
+            let repo = Repository::open(&path).expect("open repo");
+            fs::rename(path.join("notes.txt"), path.join("renamed.txt")).expect("rename");
+            fs::write(path.join("tracked.txt"), "line-1\nline-2\n").expect("write tracked");
+            let mut index = repo.index().expect("index");
+            index.remove_path(Path::new("notes.txt")).expect("remove old");
+            index.add_path(Path::new("renamed.txt")).expect("add renamed");
+            index.add_path(Path::new("tracked.txt")).expect("add tracked");
+            index.write().expect("write index");
+            let tree = repo
+                .find_tree(index.write_tree().expect("write tree"))
+                .expect("find tree");
+            let sig = Signature::now("gitpanel-test", "test@example.com").expect("signature");
+            let parent = repo.head().expect("head").peel_to_commit().expect("parent");
+            repo.commit(
+                Some("HEAD"),
+                &sig,
+                &sig,
+                "rename notes\n\nLonger body.\n",
+                &tree,
+                &[&parent],
+            )
+            .expect("commit");
+        }
+
+        let detail = commit_detail(&summary, "HEAD").expect("commit detail");
+        assert_eq!(detail.message, "rename notes\n\nLonger body.\n");
+        assert_eq!(detail.commit.summary, "rename notes");
+
+        let renamed = detail
+            .files
+            .iter()
+            .find(|file| file.path == "renamed.txt")
+            .expect("renamed file");
+        assert_eq!(renamed.change, FileChangeKind::Renamed);
+        assert_eq!(renamed.old_path.as_deref(), Some("notes.txt"));
+        assert!(renamed.hunks.is_empty());
+
+        let tracked = detail
+            .files
+            .iter()
+            .find(|file| file.path == "tracked.txt")
+            .expect("tracked file");
+        assert_eq!(tracked.change, FileChangeKind::Modified);
+        assert_eq!(tracked.hunks.len(), 1);
+        assert!(tracked.hunks[0].content.contains("+line-2"));
+        assert_eq!(
+            tracked.hunks[0].kind,
+            RepoDiffKind::Commit {
+                oid: detail.commit.oid.clone()
+            }
+        );
+
+        let single = diff_hunks_for_path(&summary, "tracked.txt", tracked.hunks[0].kind.clone())
+            .expect("commit diff for path");
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].id, tracked.hunks[0].id);
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn range_diff_resolves_merge_base_for_three_dot_ranges() {
+        let (summary, path) = init_repo_with_commit();
+        {
+            let repo = Repository::open(&path).expect("open repo");
+            let base = repo.head().expect("head").peel_to_commit().expect("base");
+            repo.branch("feature", &base, false).expect("feature branch");
+            let blob = repo.blob(b"feature\n").expect("blob");
+            let mut builder = repo
+                .treebuilder(Some(&base.tree().expect("base tree")))
+                .expect("tree builder");
+            builder
+                .insert("feature.txt", blob, 0o100644)
+                .expect("insert feature");
+            let tree = repo
+                .find_tree(builder.write().expect("write tree"))
+                .expect("find tree");
+            let sig = Signature::now("gitpanel-test", "test@example.com").expect("signature");
+            repo.commit(
+                Some("refs/heads/feature"),
+                &sig,
+                &sig,
+                "add feature",
+                &tree,
+                &[&base],
+            )
+            .expect("feature commit");
+        }
+        commit_file(&path, "main.txt", "main\n", "add main");
+
+        let resolved = resolve_range(&summary, "HEAD...feature", None).expect("resolve range");
+        let three_dot = range_diff(&summary, &resolved).expect("three-dot diff");
+        assert_eq!(three_dot.merge_base.as_deref(), Some(three_dot.from.as_str()));
+        assert_eq!(three_dot.files.len(), 1);
+        assert_eq!(three_dot.files[0].path, "feature.txt");
+        assert_eq!(three_dot.files[0].change, FileChangeKind::Added);
+        assert_eq!(three_dot.files[0].additions, 1);
+
+        let two_dot_range = resolve_range(&summary, "HEAD", Some("feature")).expect("resolve");
+        let two_dot = range_diff(&summary, &two_dot_range).expect("two-revision diff");
+        assert!(two_dot.merge_base.is_none());
+        let mut paths: Vec<(&str, FileChangeKind)> = two_dot
+            .files
+            .iter()
+            .map(|file| (file.path.as_str(), file.change))
+            .collect();
+        paths.sort_by_key(|(path, _)| *path);
+        assert_eq!(
+            paths,
+            vec![
+                ("feature.txt", FileChangeKind::Added),
+                ("main.txt", FileChangeKind::Deleted),
+            ]
+        );
+
+        let kind = RepoDiffKind::Range {
+            from: three_dot.from.clone(),
+            to: three_dot.to.clone(),
+        };
+        let hunks = diff_hunks_for_path(&summary, "feature.txt", kind).expect("range hunks");
+        assert_eq!(hunks.len(), 1);
+        assert!(hunks[0].content.contains("+feature"));
+        let deleted = diff_hunks_for_path(
+            &summary,
+            "main.txt",
+            RepoDiffKind::Range {
+                from: two_dot.from.clone(),
+                to: two_dot.to.clone(),
+            },
+        )
+        .expect("deleted file hunks");
+        assert!(deleted[0].content.contains("-main"));
+
+        let key = range_diff_cache_key(&summary, &two_dot_range);
+        assert!(key.contains(&two_dot.from) && key.contains(&two_dot.to));
+        commit_file(&path, "main.txt", "main-2\n", "edit main");
+        let moved = resolve_range(&summary, "HEAD", Some("feature")).expect("resolve moved");
+        assert_ne!(key, range_diff_cache_key(&summary, &moved));
+        let stale = range_diff(&summary, &two_dot_range).expect("diff resolved range");
+        assert_eq!(stale.from, two_dot.from);
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn blame_marks_working_tree_lines_as_not_committed() {
+        let (summary, path) = init_repo_with_commit();
+        commit_file(&path, "tracked.txt", "line-1\nline-2\nline-3\n", "add lines");
+        fs::write(path.join("tracked.txt"), "line-1\nchanged\nline-3\n").expect("edit tracked");
+
+        let at_head = blame(&summary, "tracked.txt", None, false).expect("blame head");
+        let total: u32 = at_head.ranges.iter().map(|range| range.line_count).sum();
+        assert_eq!(total, 3);
+        assert_eq!(at_head.ranges[0].summary, "initial commit");
+        assert!(at_head.ranges.iter().all(|range| range.committed));
+
+        let at_parent = blame(&summary, "tracked.txt", Some("HEAD~1"), false).expect("blame rev");
+        assert_eq!(at_parent.ranges.len(), 1);
+        assert_eq!(at_parent.ranges[0].line_count, 1);
+
+        let worktree = blame(&summary, "tracked.txt", None, true).expect("blame worktree");
+        let uncommitted: Vec<_> = worktree
+            .ranges
+            .iter()
+            .filter(|range| !range.committed)
+            .collect();
+        assert_eq!(uncommitted.len(), 1);
+        assert_eq!(uncommitted[0].start_line, 2);
+        assert_eq!(uncommitted[0].line_count, 1);
+        assert_eq!(uncommitted[0].author.name, "Not Committed Yet");
+
+        fs::write(path.join("new.txt"), "one\ntwo\n").expect("write untracked");
+        let untracked = blame(&summary, "new.txt", None, true).expect("blame untracked");
+        assert_eq!(untracked.ranges.len(), 1);
+        assert!(!untracked.ranges[0].committed);
+        assert_eq!(untracked.ranges[0].start_line, 1);
+        assert_eq!(untracked.ranges[0].line_count, 2);
+        assert!(blame(&summary, "new.txt", None, false).is_err());
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn file_history_follows_renames() {
+        let (summary, path) = init_repo_with_commit();
+        let body = "one\ntwo\nthree\nfour\nfive\nsix\n";
+        commit_file(&path, "old.txt", body, "add old");
+        commit_file(&path, "old.txt", &format!("{body}seven\n"), "edit old");
+        {
+            let repo = Repository::open(&path).expect("open repo");
+            fs::rename(path.join("old.txt"), path.join("new.txt")).expect("rename");
+            let mut index = repo.index().expect("index");
+            index.remove_path(Path::new("old.txt")).expect("remove old");
+            index.add_path(Path::new("new.txt")).expect("add new");
+            index.write().expect("write index");
+            let tree = repo
+                .find_tree(index.write_tree().expect("write tree"))
+                .expect("find tree");
+            let sig = Signature::now("gitpanel-test", "test@example.com").expect("signature");
+            let parent = repo.head().expect("head").peel_to_commit().expect("parent");
+            repo.commit(Some("HEAD"), &sig, &sig, "rename", &tree, &[&parent])
+                .expect("commit");
+        }
+        commit_file(&path, "tracked.txt", "line-1\nline-2\n", "unrelated");
+        commit_file(&path, "new.txt", &format!("{body}seven\neight\n"), "edit new");
+
+        let first = file_history(&summary, "new.txt", None, None, Some(2)).expect("first page");
+        let seen: Vec<(&str, &str, FileChangeKind)> = first
+            .entries
+            .iter()
+            .map(|entry| (entry.commit.summary.as_str(), entry.path.as_str(), entry.change))
+            .collect();
+        assert_eq!(
+            seen,
+            vec![
+                ("edit new", "new.txt", FileChangeKind::Modified),
+                ("rename", "new.txt", FileChangeKind::Renamed),
+            ]
+        );
+        assert_eq!(first.entries[1].old_path.as_deref(), Some("old.txt"));
+
+        let cursor = first.next_cursor.clone().expect("cursor");
+        let second = file_history(&summary, "new.txt", None, Some(&cursor), Some(2))
+            .expect("second page");
+        let seen: Vec<(&str, &str, FileChangeKind)> = second
+            .entries
+            .iter()
+            .map(|entry| (entry.commit.summary.as_str(), entry.path.as_str(), entry.change))
+            .collect();
+        assert_eq!(
+            seen,
+            vec![
+                ("edit old", "old.txt", FileChangeKind::Modified),
+                ("add old", "old.txt", FileChangeKind::Added),
+            ]
+        );
+        assert!(second.next_cursor.is_none());
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn resolve_conflict_takes_a_side_and_clears_stages() {
+        let (summary, path) = init_repo_with_conflict();
+
+        let versions = conflict_versions(&summary, "tracked.txt").expect("versions");
+        let content = |blob: Option<&crate::model::ConflictBlob>| {
+            blob.and_then(|blob| blob.content.clone())
+        };
+        assert_eq!(content(versions.base.as_ref()).as_deref(), Some("line-1\n"));
+        assert_eq!(content(versions.ours.as_ref()).as_deref(), Some("line-ours\n"));
+        assert_eq!(content(versions.theirs.as_ref()).as_deref(), Some("line-theirs\n"));
+
+        resolve_conflict(&summary, "tracked.txt", &ConflictResolution::Theirs)
+            .expect("resolve theirs");
+        assert_eq!(
+            fs::read_to_string(path.join("tracked.txt")).expect("read tracked"),
+            "line-theirs\n"
+        );
+        let repo = Repository::open(&path).expect("open repo");
+        assert!(!repo.index().expect("index").has_conflicts());
+        let state = status(&summary).expect("status");
+        assert_eq!(state.counts.conflicted, 0);
+        assert!(conflict_versions(&summary, "tracked.txt").is_err());
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn resolve_conflict_writes_merged_content() {
+        let (summary, path) = init_repo_with_conflict();
+        let merged = ConflictResolution::Merged {
+            content: "line-ours\nline-theirs\n".to_string(),
+        };
+
+        resolve_conflict(&summary, "tracked.txt", &merged).expect("resolve merged");
+
+        assert_eq!(
+            fs::read_to_string(path.join("tracked.txt")).expect("read tracked"),
+            "line-ours\nline-theirs\n"
+        );
+        let repo = Repository::open(&path).expect("open repo");
+        let index = repo.index().expect("index");
+        assert!(!index.has_conflicts());
+        assert!(index.get_path(Path::new("tracked.txt"), 0).is_some());
+
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn merge_operation_is_reported_and_continued() {
+        let (summary, path) = init_repo_with_conflict();
+        let state = status(&summary).expect("status");
+        assert_eq!(state.operation, RepoOperation::Merge);
+        assert!(state.rebase_progress.is_none());
+
+        match operation_continue(&summary) {
+            Err(RepoError::MergeConflict { paths, .. }) => {
+                assert_eq!(paths, vec!["tracked.txt".to_string()]);
             }
-            token.push(*c);
-            chars.next();
+            other => panic!("expected unresolved conflicts, got {other:?}"),
         }
-        if !token.is_empty() {
-            tokens.push(token);
-        }
-    }
 
-    tokens
-}
+        resolve_conflict(&summary, "tracked.txt", &ConflictResolution::Ours).expect("resolve");
+        operation_continue(&summary).expect("continue merge");
+        let state = status(&summary).expect("status");
+        assert_eq!(state.operation, RepoOperation::None);
+        let repo = Repository::open(&path).expect("open repo");
+        let head = repo.head().expect("head").peel_to_commit().expect("head commit");
+        assert_eq!(head.parent_count(), 2);
 
-fn parse_hunk_header(line: &str) -> (u32, u32, u32, u32) {
-    let header = line.trim_start_matches("@@ ").trim_end_matches("@@");
-    let mut parts = header.split(" @@").next().unwrap_or("").split_whitespace();
-    let old_part = parts.next().unwrap_or("-0");
-    let new_part = parts.next().unwrap_or("+0");
-    let (old_start, old_lines) = parse_range(old_part.trim_start_matches('-'));
-    let (new_start, new_lines) = parse_range(new_part.trim_start_matches('+'));
-    (old_start, old_lines, new_start, new_lines)
-}
+        let _ = fs::remove_dir_all(path);
+    }
 
-fn parse_range(text: &str) -> (u32, u32) {
-    let mut parts = text.split(',');
-    let start = parts
-        .next()
-        .and_then(|value| value.parse::<u32>().ok())
-        .unwrap_or(0);
-    let lines = parts
-        .next()
-        .and_then(|value| value.parse::<u32>().ok())
-        .unwrap_or(1);
-    (start, lines)
-}
+    #[test]
+    fn rebase_progress_is_reported_and_skip_advances() {
+        let (summary, path) = init_repo_with_commit();
+        set_identity(&path);
+        run_git(&summary.path, &["checkout", "-q", "-b", "topic"], None).expect("branch");
+        commit_file(&path, "tracked.txt", "line-topic\n", "topic edit");
+        commit_file(&path, "topic.txt", "topic\n", "topic file");
+        run_git(&summary.path, &["checkout", "-q", "-"], None).expect("checkout back");
+        commit_file(&path, "tracked.txt", "line-main\n", "main edit");
+        run_git(&summary.path, &["checkout", "-q", "topic"], None).expect("checkout topic");
+
+        assert!(run_git(&summary.path, &["rebase", "-"], None).is_err());
+        let state = status(&summary).expect("status");
+        assert!(matches!(
+            state.operation,
+            RepoOperation::RebaseMerge | RepoOperation::RebaseInteractive | RepoOperation::Rebase
+        ));
+        let progress = state.rebase_progress.expect("rebase progress");
+        assert_eq!((progress.current, progress.total), (1, 2));
+
+        operation_skip(&summary).expect("skip conflicting step");
+        let state = status(&summary).expect("status");
+        assert_eq!(state.operation, RepoOperation::None);
+        assert!(path.join("topic.txt").exists());
+        assert_eq!(
+            fs::read_to_string(path.join("tracked.txt")).expect("read tracked"),
+            "line-main\n"
+        );
 
-fn hash_content(text: &str) -> String {
-    let mut hash: u64 = 0xcbf29ce484222325;
-    for byte in text.as_bytes() {
-        hash ^= u64::from(*byte);
-        hash = hash.wrapping_mul(0x100000001b3);
+        let _ = fs::remove_dir_all(path);
     }
-    format!("{hash:016x}")
-}
-
-#[cfg(test)]
-mod tests {
-    use super::{parse_diff_hunks, stage_path, status, track_path, unstage_path, RepoDiffKind};
-    use crate::model::{RepoSummary, StatusKind};
-    use git2::{Repository, Signature};
-    use std::fs;
-    use std::path::{Path, PathBuf};
-    use std::time::{SystemTime, UNIX_EPOCH};
 
     #[test]
-    fn hunk_id_changes_with_content() {
-        let diff = "\
-diff --git a/foo.txt b/foo.txt\n\
-index 1111111..2222222 100644\n\
---- a/foo.txt\n\
-+++ b/foo.txt\n\
-@@ -1,2 +1,2 @@\n\
--hello\n\
-+hello world\n\
- line2\n";
+    fn operation_abort_restores_pre_merge_state() {
+        let (summary, path) = init_repo_with_conflict();
 
-        let hunks = parse_diff_hunks(diff, "foo.txt", RepoDiffKind::Unstaged);
-        assert_eq!(hunks.len(), 1);
-        let first_id = hunks[0].id.clone();
+        assert!(operation_skip(&summary).is_err());
+        operation_abort(&summary).expect("abort merge");
 
-        let diff_changed = "\
-diff --git a/foo.txt b/foo.txt\n\
-index 1111111..2222222 100644\n\
---- a/foo.txt\n\
-+++ b/foo.txt\n\
-@@ -1,2 +1,2 @@\n\
--hello\n\
-+hello brave world\n\
- line2\n";
-        let hunks_changed = parse_diff_hunks(diff_changed, "foo.txt", RepoDiffKind::Unstaged);
-        assert_eq!(hunks_changed.len(), 1);
-        assert_ne!(first_id, hunks_changed[0].id);
+        let state = status(&summary).expect("status");
+        assert_eq!(state.operation, RepoOperation::None);
+        assert_eq!(state.counts.conflicted, 0);
+        assert_eq!(
+            fs::read_to_string(path.join("tracked.txt")).expect("read tracked"),
+            "line-ours\n"
+        );
+        assert!(operation_abort(&summary).is_err());
+
+        let _ = fs::remove_dir_all(path);
     }
 
-    fn temp_repo_path() -> PathBuf {
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("clock")
-            .as_nanos();
-        std::env::temp_dir().join(format!("gitpanel-git-test-{nanos}"))
+    #[test]
+    fn stash_save_show_and_pop_round_trip() {
+        let (summary, path) = init_repo_with_commit();
+        set_identity(&path);
+        fs::write(path.join("tracked.txt"), "line-1\nline-2\n").expect("edit tracked");
+        fs::write(path.join("scratch.txt"), "scratch\n").expect("write untracked");
+
+        let entry = stash_save(&summary, Some("wip"), true, false).expect("stash save");
+        assert_eq!(entry.index, 0);
+        assert!(entry.message.contains("wip"));
+        assert!(!path.join("scratch.txt").exists());
+        assert_eq!(
+            fs::read_to_string(path.join("tracked.txt")).expect("read tracked"),
+            "line-1\n"
+        );
+        assert_eq!(stash_list(&summary).expect("stash list").len(), 1);
+
+        let detail = stash_show(&summary, 0).expect("stash show");
+        let mut paths: Vec<&str> = detail.files.iter().map(|file| file.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["scratch.txt", "tracked.txt"]);
+        let tracked = detail
+            .files
+            .iter()
+            .find(|file| file.path == "tracked.txt")
+            .expect("tracked change");
+        assert!(tracked.hunks[0].content.contains("+line-2"));
+
+        assert!(stash_pop(&summary, 0, &"1".repeat(40)).is_err());
+        assert!(!path.join("scratch.txt").exists());
+        stash_pop(&summary, 0, &entry.oid).expect("stash pop");
+        assert!(path.join("scratch.txt").exists());
+        assert!(stash_list(&summary).expect("stash list").is_empty());
+
+        let tracked_only = stash_save(&summary, None, false, false).expect("stash tracked only");
+        assert!(path.join("scratch.txt").exists());
+        stash_apply(&summary, 0, &tracked_only.oid).expect("stash apply");
+        assert_eq!(stash_list(&summary).expect("stash list").len(), 1);
+        assert!(stash_drop(&summary, 0, &entry.oid).is_err());
+        stash_drop(&summary, 0, &tracked_only.oid).expect("stash drop");
+        assert!(stash_list(&summary).expect("stash list").is_empty());
+
+        let _ = fs::remove_dir_all(path);
     }
 
-    fn repo_summary(path: &Path) -> RepoSummary {
-        let path_text = path.to_string_lossy().to_string();
-        RepoSummary {
-            repo_id: "test-repo".to_string(),
-            path: path_text.clone(),
-            name: "test".to_string(),
-            repo_root: path_text.clone(),
-            worktree_path: path_text,
-            is_valid: true,
+    fn local_target(name: &str) -> CheckoutTarget {
+        CheckoutTarget {
+            kind: CheckoutTargetKind::Local,
+            name: name.to_string(),
         }
     }
 
-    fn init_repo_with_commit() -> (RepoSummary, PathBuf) {
-        let path = temp_repo_path();
-        fs::create_dir_all(&path).expect("create repo root");
-        let repo = Repository::init(&path).expect("init repo");
+    #[test]
+    fn stash_checkout_reapplies_changes_and_reports_conflicts() {
+        let (summary, path) = init_repo_with_commit();
+        set_identity(&path);
+        run_git(&summary.path, &["checkout", "-q", "-b", "feature"], None).expect("branch");
+        commit_file(&path, "tracked.txt", "line-feature\n", "feature");
+        run_git(&summary.path, &["checkout", "-q", "-"], None).expect("checkout back");
+
+        fs::write(path.join("notes.txt"), "notes\n").expect("write untracked");
+        let dirty = checkout_branch(&summary, &local_target("feature"), CheckoutMode::Clean);
+        assert!(matches!(dirty, Err(RepoError::DirtyWorkingTree { .. })));
+
+        let result = checkout_branch(&summary, &local_target("feature"), CheckoutMode::Stash)
+            .expect("stash checkout");
+        assert_eq!(result.head.branch_name.as_deref(), Some("feature"));
+        assert!(result.conflicts.is_empty());
+        assert!(result.stash.is_none());
+        assert!(path.join("notes.txt").exists());
+        assert!(stash_list(&summary).expect("stash list").is_empty());
+
+        fs::remove_file(path.join("notes.txt")).expect("remove untracked");
+        run_git(&summary.path, &["checkout", "-q", "-"], None).expect("checkout back");
+        fs::write(path.join("tracked.txt"), "line-local\n").expect("edit tracked");
+        let result = checkout_branch(&summary, &local_target("feature"), CheckoutMode::Stash)
+            .expect("conflicting stash checkout");
+        assert_eq!(result.head.branch_name.as_deref(), Some("feature"));
+        assert_eq!(result.conflicts, vec!["tracked.txt".to_string()]);
+        assert!(result.stash.is_some());
+        assert_eq!(stash_list(&summary).expect("stash list").len(), 1);
 
-        fs::write(path.join("tracked.txt"), "line-1\n").expect("write tracked");
+        let _ = fs::remove_dir_all(path);
+    }
 
-        let mut index = repo.index().expect("index");
-        index
-            .add_path(Path::new("tracked.txt"))
-            .expect("stage tracked");
-        index.write().expect("write index");
+    #[test]
+    fn carry_checkout_keeps_unrelated_changes_and_refuses_overwrites() {
+        let (summary, path) = init_repo_with_commit();
+        set_identity(&path);
+        run_git(&summary.path, &["checkout", "-q", "-b", "feature"], None).expect("branch");
+        commit_file(&path, "feature.txt", "feature\n", "feature");
+        run_git(&summary.path, &["checkout", "-q", "-"], None).expect("checkout back");
+        let original = status(&summary)
+            .expect("status")
+            .head
+            .branch_name
+            .expect("branch");
+
+        fs::write(path.join("tracked.txt"), "line-1\nline-2\n").expect("edit tracked");
+        let result = checkout_branch(&summary, &local_target("feature"), CheckoutMode::Carry)
+            .expect("carry checkout");
+        assert_eq!(result.head.branch_name.as_deref(), Some("feature"));
+        assert_eq!(
+            fs::read_to_string(path.join("tracked.txt")).expect("read tracked"),
+            "line-1\nline-2\n"
+        );
+
+        fs::write(path.join("feature.txt"), "local\n").expect("edit feature file");
+        let refused = checkout_branch(&summary, &local_target(&original), CheckoutMode::Carry);
+        match refused {
+            Err(RepoError::CheckoutConflict { paths, .. }) => {
+                assert_eq!(paths, vec!["feature.txt".to_string()]);
+            }
+            other => panic!("expected checkout conflict, got {other:?}"),
+        }
+        assert_eq!(
+            status(&summary).expect("status").head.branch_name.as_deref(),
+            Some("feature")
+        );
 
-        let tree_id = index.write_tree().expect("write tree");
-        let tree = repo.find_tree(tree_id).expect("find tree");
-        let sig = Signature::now("gitpanel-test", "test@example.com").expect("signature");
-        repo.commit(Some("HEAD"), &sig, &sig, "initial commit", &tree, &[])
-            .expect("commit");
+        let _ = fs::remove_dir_all(path);
+    }
 
-        (repo_summary(&path), path)
+    #[test]
+    fn tag_and_commit_checkout_detach_head() {
+        let (summary, path) = init_repo_with_commit();
+        set_identity(&path);
+        run_git(&summary.path, &["tag", "v1"], None).expect("tag");
+        let first = run_git(&summary.path, &["rev-parse", "HEAD"], None).expect("rev-parse");
+        commit_file(&path, "tracked.txt", "line-2\n", "second");
+
+        let tag = CheckoutTarget {
+            kind: CheckoutTargetKind::Tag,
+            name: "v1".to_string(),
+        };
+        let result = checkout_branch(&summary, &tag, CheckoutMode::Clean).expect("tag checkout");
+        assert!(result.head.detached);
+        assert!(result.head.branch_name.is_none());
+        assert_eq!(result.head.oid_short, first.trim()[..7]);
+        assert_eq!(list_branches(&summary).expect("branches").current, "");
+
+        let commit = CheckoutTarget {
+            kind: CheckoutTargetKind::Commit,
+            name: "HEAD@{1}".to_string(),
+        };
+        let result =
+            checkout_branch(&summary, &commit, CheckoutMode::Clean).expect("commit checkout");
+        assert!(result.head.detached);
+        assert_ne!(result.head.oid_short, first.trim()[..7]);
+
+        fs::write(path.join("tracked.txt"), "line-wip\n").expect("edit tracked");
+        let head = create_branch(&summary, "rescued", None, true)
+            .expect("create branch")
+            .expect("checked out");
+        assert!(!head.detached);
+        assert_eq!(head.branch_name.as_deref(), Some("rescued"));
+        assert_eq!(
+            fs::read_to_string(path.join("tracked.txt")).expect("read tracked"),
+            "line-wip\n"
+        );
+
+        let _ = fs::remove_dir_all(path);
     }
 
     #[test]
-    fn unstage_newly_added_file_removes_index_entry() {
+    fn branch_delete_rename_and_upstream_management() {
         let (summary, path) = init_repo_with_commit();
-        fs::write(path.join("new.txt"), "new-line\n").expect("write new");
+        set_identity(&path);
+        let remote_path = temp_repo_path();
+        Repository::init_bare(&remote_path).expect("init bare");
+        Repository::open(&path)
+            .expect("open repo")
+            .remote("origin", &remote_path.to_string_lossy())
+            .expect("add remote");
+        let current = status(&summary)
+            .expect("status")
+            .head
+            .branch_name
+            .expect("branch");
+
+        run_git(&summary.path, &["checkout", "-q", "-b", "topic"], None).expect("branch");
+        commit_file(&path, "topic.txt", "topic\n", "topic");
+        let checked_out = delete_branch(&summary, "topic", false);
+        assert!(matches!(checked_out, Err(RepoError::BranchCheckedOut { .. })));
+        run_git(&summary.path, &["checkout", "-q", &current], None).expect("checkout back");
+
+        let unmerged = delete_branch(&summary, "topic", false);
+        assert!(matches!(unmerged, Err(RepoError::BranchNotMerged { .. })));
+        create_branch(&summary, "other", None, false).expect("create other");
+        let exists = rename_branch(&summary, "topic", "other");
+        assert!(matches!(exists, Err(RepoError::BranchExists { .. })));
+        rename_branch(&summary, "topic", "renamed").expect("rename");
+        delete_branch(&summary, "other", false).expect("delete merged");
+
+        push(&summary, None, Some("renamed"), false, None).expect("push renamed");
+        set_upstream(&summary, "renamed", Some("origin/renamed")).expect("set upstream");
+        let branches = list_branches(&summary).expect("branches");
+        assert_eq!(
+            branches.upstreams.get("renamed").map(String::as_str),
+            Some("origin/renamed")
+        );
+        set_upstream(&summary, "renamed", None).expect("unset upstream");
+        let branches = list_branches(&summary).expect("branches");
+        assert!(!branches.upstreams.contains_key("renamed"));
+
+        delete_remote_branch(&summary, None, "renamed", None).expect("delete remote branch");
+        let bare = Repository::open_bare(&remote_path).expect("open bare");
+        assert!(bare.find_reference("refs/heads/renamed").is_err());
+        let branches = list_branches(&summary).expect("branches");
+        assert!(!branches.remotes.contains(&"origin/renamed".to_string()));
+        delete_branch(&summary, "renamed", true).expect("force delete");
 
-        stage_path(&summary, "new.txt").expect("stage new");
+        let _ = fs::remove_dir_all(path);
+        let _ = fs::remove_dir_all(remote_path);
+    }
 
-        {
-            let repo = Repository::open(&summary.path).expect("open repo");
-            let index = repo.index().expect("open index");
-            assert!(index.get_path(Path::new("new.txt"), 0).is_some());
-        }
+    #[test]
+    fn tags_are_listed_created_deleted_and_pushed() {
+        let (summary, path) = init_repo_with_commit();
+        set_identity(&path);
+        let remote_path = temp_repo_path();
+        Repository::init_bare(&remote_path).expect("init bare");
+        Repository::open(&path)
+            .expect("open repo")
+            .remote("origin", &remote_path.to_string_lossy())
+            .expect("add remote");
+        let first = run_git(&summary.path, &["rev-parse", "HEAD"], None).expect("rev-parse");
+        commit_file(&path, "tracked.txt", "line-2\n", "second");
+
+        let light = create_tag(&summary, "v1", Some("HEAD~1"), None).expect("lightweight tag");
+        assert!(!light.annotated);
+        assert_eq!(light.target, first.trim());
+        let annotated =
+            create_tag(&summary, "v2", None, Some("Release v2\n")).expect("annotated tag");
+        assert!(annotated.annotated);
+        assert_eq!(annotated.message.as_deref(), Some("Release v2"));
+        assert_eq!(
+            annotated.tagger.as_ref().map(|tagger| tagger.name.as_str()),
+            Some("gitpanel-test")
+        );
+        assert!(create_tag(&summary, "v2", None, None).is_err());
+
+        let names: Vec<String> = list_branches(&summary)
+            .expect("branches")
+            .tags
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect();
+        assert_eq!(names, vec!["v1".to_string(), "v2".to_string()]);
+
+        push_tags(&summary, None, Some("v1"), None).expect("push one tag");
+        let bare = Repository::open_bare(&remote_path).expect("open bare");
+        assert!(bare.find_reference("refs/tags/v1").is_ok());
+        assert!(bare.find_reference("refs/tags/v2").is_err());
+        push_tags(&summary, None, None, None).expect("push all tags");
+        assert!(bare.find_reference("refs/tags/v2").is_ok());
+
+        delete_tag(&summary, "v1").expect("delete tag");
+        let tags = list_tags(&summary).expect("tags");
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].name, "v2");
 
-        unstage_path(&summary, "new.txt").expect("unstage new");
+        let _ = fs::remove_dir_all(path);
+        let _ = fs::remove_dir_all(remote_path);
+    }
 
-        {
-            let repo = Repository::open(&summary.path).expect("open repo");
-            let index = repo.index().expect("open index");
-            assert!(index.get_path(Path::new("new.txt"), 0).is_none());
+    #[test]
+    fn revert_and_cherry_pick_commit_or_stage_changes() {
+        let (summary, path) = init_repo_with_commit();
+        set_identity(&path);
+        run_git(&summary.path, &["checkout", "-q", "-b", "feature"], None).expect("branch");
+        commit_file(&path, "picked.txt", "picked\n", "add picked");
+        let picked = run_git(&summary.path, &["rev-parse", "HEAD"], None).expect("rev-parse");
+        run_git(&summary.path, &["checkout", "-q", "-"], None).expect("checkout back");
+
+        let staged = cherry_pick(&summary, picked.trim(), true).expect("cherry-pick -n");
+        assert!(staged.commit_id.is_none());
+        assert_eq!(staged.paths, vec!["picked.txt".to_string()]);
+        assert!(path.join("picked.txt").exists());
+        reset(&summary, "HEAD", ResetMode::Mixed, None).expect("unstage");
+        fs::remove_file(path.join("picked.txt")).expect("remove picked");
+
+        let committed = cherry_pick(&summary, picked.trim(), false).expect("cherry-pick");
+        let commit_id = committed.commit_id.expect("new commit");
+        assert!(commit_id.starts_with(&committed.head.oid_short));
+
+        let reverted = revert(&summary, &commit_id, false).expect("revert");
+        assert!(reverted.commit_id.is_some());
+        assert!(!path.join("picked.txt").exists());
+
+        run_git(&summary.path, &["checkout", "-q", "feature"], None).expect("checkout feature");
+        commit_file(&path, "tracked.txt", "line-feature\n", "feature edit");
+        run_git(&summary.path, &["checkout", "-q", "-"], None).expect("checkout back");
+        commit_file(&path, "tracked.txt", "line-ours\n", "ours");
+        match cherry_pick(&summary, "feature", false) {
+            Err(RepoError::MergeConflict { paths, .. }) => {
+                assert_eq!(paths, vec!["tracked.txt".to_string()]);
+            }
+            other => panic!("expected cherry-pick conflict, got {other:?}"),
         }
+        assert_eq!(
+            status(&summary).expect("status").operation,
+            RepoOperation::CherryPick
+        );
+        operation_abort(&summary).expect("abort cherry-pick");
 
-        let repo_status = status(&summary).expect("status");
-        let entry = repo_status
-            .files
-            .iter()
-            .find(|file| file.path == "new.txt")
-            .expect("new file in status");
-        assert!(matches!(entry.status, StatusKind::Untracked));
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn hard_reset_requires_confirmation_and_writes_backup_ref() {
+        let (summary, path) = init_repo_with_commit();
+        set_identity(&path);
+        commit_file(&path, "tracked.txt", "line-2\n", "second");
+        fs::write(path.join("tracked.txt"), "line-wip\n").expect("edit tracked");
+
+        let soft = reset(&summary, "HEAD~1", ResetMode::Soft, None).expect("soft reset");
+        assert_eq!(
+            fs::read_to_string(path.join("tracked.txt")).expect("read tracked"),
+            "line-wip\n"
+        );
+        let head = run_git(&summary.path, &["rev-parse", "HEAD"], None).expect("rev-parse");
+        assert!(head.trim().starts_with(&soft.head.oid_short));
+        run_git(&summary.path, &["reset", "-q", "--soft", "HEAD@{1}"], None).expect("restore");
+
+        let token = match reset(&summary, "HEAD~1", ResetMode::Hard, None) {
+            Err(RepoError::ConfirmationRequired { token, .. }) => token,
+            other => panic!("expected confirmation request, got {other:?}"),
+        };
+        assert!(reset(&summary, "HEAD~1", ResetMode::Hard, Some("nope")).is_err());
+        let result = reset(&summary, "HEAD~1", ResetMode::Hard, Some(&token)).expect("hard reset");
+        assert_eq!(
+            fs::read_to_string(path.join("tracked.txt")).expect("read tracked"),
+            "line-1\n"
+        );
+
+        let backup = result.backup_ref.expect("backup ref");
+        let spec = format!("{backup}:tracked.txt");
+        let saved = run_git(&summary.path, &["show", &spec], None).expect("show backup");
+        assert_eq!(saved, "line-wip\n");
+        let parent = format!("{backup}^1");
+        let saved_head = run_git(&summary.path, &["rev-parse", &parent], None).expect("parent");
+        assert_eq!(saved_head.trim(), token);
 
         let _ = fs::remove_dir_all(path);
     }
 
     #[test]
-    fn unstage_tracked_file_keeps_worktree_change() {
+    fn commits_are_signed_with_ssh_keys_when_configured() {
         let (summary, path) = init_repo_with_commit();
-        fs::write(path.join("tracked.txt"), "line-2\n").expect("mutate tracked");
+        set_identity(&path);
+        let key_path = path.join(".git").join("signing_key");
+        let key_text = key_path.to_string_lossy().to_string();
+        let generated = std::process::Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", "test", "-f", &key_text])
+            .status()
+            .expect("run ssh-keygen");
+        assert!(generated.success());
+        let public_key = fs::read_to_string(format!("{key_text}.pub")).expect("read public key");
+        let signers = path.join(".git").join("allowed_signers");
+        fs::write(&signers, format!("test@example.com {public_key}")).expect("write signers");
+
+        let repo = Repository::open(&path).expect("open repo");
+        let mut config = repo.config().expect("config");
+        config.set_str("gpg.format", "ssh").expect("gpg format");
+        config
+            .set_str("gpg.ssh.allowedSignersFile", &signers.to_string_lossy())
+            .expect("allowed signers");
+        config.set_bool("commit.gpgsign", true).expect("gpgsign");
+
+        let tracked = unstaged_file("tracked.txt");
+        fs::write(path.join("tracked.txt"), "line-2\n").expect("edit tracked");
+        let files = std::slice::from_ref(&tracked);
+        let missing_key = commit_changelist(&summary, files, "signed", &CommitOptions::default());
+        assert!(missing_key.is_err());
+
+        config.set_str("user.signingkey", &key_text).expect("signing key");
+        let signed = commit_changelist(&summary, files, "signed", &CommitOptions::default())
+            .expect("signed commit");
+        assert_eq!(signed.signature, SignatureStatus::Verified);
+
+        fs::write(path.join("tracked.txt"), "line-3\n").expect("edit tracked");
+        let options = CommitOptions {
+            sign: Some(false),
+            ..CommitOptions::default()
+        };
+        let unsigned =
+            commit_changelist(&summary, files, "unsigned", &options).expect("unsigned commit");
+        assert_eq!(unsigned.signature, SignatureStatus::Unsigned);
 
-        stage_path(&summary, "tracked.txt").expect("stage tracked");
-        unstage_path(&summary, "tracked.txt").expect("unstage tracked");
+        let _ = fs::remove_dir_all(path);
+    }
 
-        {
-            let repo = Repository::open(&summary.path).expect("open repo");
-            let index = repo.index().expect("open index");
-            let index_entry = index
-                .get_path(Path::new("tracked.txt"), 0)
-                .expect("tracked in index");
-            let head_commit = repo
-                .head()
-                .expect("head")
-                .peel_to_commit()
-                .expect("head commit");
-            let head_tree = head_commit.tree().expect("head tree");
-            let head_entry = head_tree
-                .get_path(Path::new("tracked.txt"))
-                .expect("tracked in head");
-            assert_eq!(index_entry.id, head_entry.id());
+    #[cfg(unix)]
+    #[test]
+    fn commit_hooks_run_against_the_temporary_index() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (summary, path) = init_repo_with_commit();
+        set_identity(&path);
+        let hooks = path.join(".git").join("hooks");
+        fs::create_dir_all(&hooks).expect("hooks dir");
+        let write_hook = |name: &str, body: &str| {
+            let hook = hooks.join(name);
+            fs::write(&hook, format!("#!/bin/sh\n{body}\n")).expect("write hook");
+            fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).expect("chmod hook");
+        };
+        write_hook(
+            "pre-commit",
+            "git diff --cached --name-only > .git/pre-commit-files\n\
+             if git diff --cached | grep -q forbidden; then echo 'lint failed'; exit 1; fi",
+        );
+        write_hook("commit-msg", "echo 'Ticket: GP-1' >> \"$1\"");
+        write_hook("post-commit", "git rev-parse HEAD > .git/post-commit-head");
+
+        let tracked = unstaged_file("tracked.txt");
+        let files = std::slice::from_ref(&tracked);
+        fs::write(path.join("tracked.txt"), "line-2\n").expect("edit tracked");
+        fs::write(path.join("other.txt"), "not in changelist\n").expect("write other");
+        let result = commit_changelist(&summary, files, "hooked\n", &CommitOptions::default())
+            .expect("commit");
+        let staged = fs::read_to_string(path.join(".git").join("pre-commit-files"))
+            .expect("pre-commit ran");
+        assert_eq!(staged.trim(), "tracked.txt");
+        let repo = Repository::open(&path).expect("open repo");
+        let head = repo.head().expect("head").peel_to_commit().expect("commit");
+        assert_eq!(head.message(), Some("hooked\nTicket: GP-1\n"));
+        let post_head = fs::read_to_string(path.join(".git").join("post-commit-head"))
+            .expect("post-commit ran");
+        assert_eq!(post_head.trim(), result.commit_id);
+
+        fs::write(path.join("tracked.txt"), "forbidden\n").expect("edit tracked");
+        let rejected = commit_changelist(&summary, files, "blocked", &CommitOptions::default());
+        match rejected {
+            Err(RepoError::HookFailed {
+                hook,
+                exit_code,
+                output,
+                ..
+            }) => {
+                assert_eq!(hook, "pre-commit");
+                assert_eq!(exit_code, Some(1));
+                assert_eq!(output, "lint failed");
+            }
+            other => panic!("expected hook failure, got {other:?}"),
         }
 
-        let repo_status = status(&summary).expect("status");
-        assert_eq!(repo_status.counts.staged, 0);
-        assert!(repo_status
-            .files
-            .iter()
-            .any(|file| file.path == "tracked.txt"));
+        let options = CommitOptions {
+            no_verify: true,
+            ..CommitOptions::default()
+        };
+        commit_changelist(&summary, files, "bypassed", &options).expect("no-verify commit");
+        let head = repo.head().expect("head").peel_to_commit().expect("commit");
+        assert_eq!(head.message(), Some("bypassed\n"));
 
         let _ = fs::remove_dir_all(path);
     }
 
     #[test]
-    fn track_new_file_marks_it_as_unstaged() {
+    fn commit_author_overrides_and_trailers() {
         let (summary, path) = init_repo_with_commit();
-        fs::write(path.join("intent.txt"), "intent\n").expect("write intent");
-
-        track_path(&summary, "intent.txt").expect("track file");
+        set_identity(&path);
+        let tracked = unstaged_file("tracked.txt");
+        let files = std::slice::from_ref(&tracked);
+
+        fs::write(path.join("tracked.txt"), "line-2\n").expect("edit tracked");
+        let options = CommitOptions {
+            author: Some(CommitAuthor {
+                name: Some("Pair Author".to_string()),
+                email: Some("pair@example.com".to_string()),
+                date: Some("2024-01-02T03:04:05+02:00".to_string()),
+            }),
+            signoff: true,
+            trailers: vec![CommitTrailer {
+                key: "Co-authored-by".to_string(),
+                value: "Helper <helper@example.com>".to_string(),
+            }],
+            ..CommitOptions::default()
+        };
+        let result =
+            commit_changelist(&summary, files, "Add line\n\nBody text.", &options).expect("commit");
+        assert_eq!(result.author.name, "Pair Author");
+        assert_eq!(result.author.email, "pair@example.com");
+        assert_eq!(result.author.time, 1704157445);
+        assert_eq!(result.author.offset_minutes, 120);
+        assert_eq!(result.committer.name, "gitpanel-test");
+        let repo = Repository::open(&path).expect("open repo");
+        let head = repo.head().expect("head").peel_to_commit().expect("commit");
+        assert_eq!(
+            head.message(),
+            Some(
+                "Add line\n\nBody text.\n\n\
+                 Signed-off-by: gitpanel-test <test@example.com>\n\
+                 Co-authored-by: Helper <helper@example.com>\n"
+            )
+        );
 
-        let repo_status = status(&summary).expect("status");
-        let entry = repo_status
-            .files
-            .iter()
-            .find(|file| file.path == "intent.txt")
-            .expect("intent file in status");
-        assert!(matches!(entry.status, StatusKind::Unstaged));
-        assert_eq!(repo_status.counts.staged, 0);
-        assert_eq!(repo_status.counts.untracked, 0);
+        fs::write(path.join("tracked.txt"), "line-3\n").expect("edit tracked");
+        let amend = CommitOptions {
+            amend: true,
+            ..CommitOptions::default()
+        };
+        let amended = commit_changelist(&summary, files, "Amended", &amend).expect("amend");
+        assert_eq!(amended.author.name, "Pair Author");
+        assert_eq!(amended.author.time, 1704157445);
+
+        let reset = CommitOptions {
+            amend: true,
+            reset_author: true,
+            ..CommitOptions::default()
+        };
+        let reset = commit_changelist(&summary, files, "Reset", &reset).expect("reset author");
+        assert_eq!(reset.author.name, "gitpanel-test");
+        assert_ne!(reset.author.time, 1704157445);
+
+        let invalid = CommitOptions {
+            trailers: vec![CommitTrailer {
+                key: "Bad Key".to_string(),
+                value: "x".to_string(),
+            }],
+            ..CommitOptions::default()
+        };
+        fs::write(path.join("tracked.txt"), "line-4\n").expect("edit tracked");
+        assert!(commit_changelist(&summary, files, "Invalid", &invalid).is_err());
 
         let _ = fs::remove_dir_all(path);
     }
@@ -1239,14 +4972,54 @@ fn is_workdir_dirty(repo: &Repository) -> bool {
     }
 }
 
-fn checkout_local(repo: &Repository, name: &str) -> Result<(), RepoError> {
+fn checkout_target(repo: &Repository, target: &CheckoutTarget) -> Result<(), RepoError> {
+    let conflicts = RefCell::new(Vec::new());
+    let mut builder = CheckoutBuilder::new();
+    builder
+        .safe()
+        .notify_on(CheckoutNotificationType::CONFLICT)
+        .notify(|_, path, _, _, _| {
+            if let Some(path) = path {
+                conflicts
+                    .borrow_mut()
+                    .push(path.to_string_lossy().replace('\\', "/"));
+            }
+            true
+        });
+
+    let result = match target.kind {
+        CheckoutTargetKind::Local => checkout_local(repo, &target.name, &mut builder),
+        CheckoutTargetKind::Remote => checkout_remote(repo, &target.name, &mut builder),
+        CheckoutTargetKind::Tag => {
+            checkout_detached(repo, &format!("refs/tags/{}", target.name), &mut builder)
+        }
+        CheckoutTargetKind::Commit => checkout_detached(repo, &target.name, &mut builder),
+    };
+    drop(builder);
+    let paths = conflicts.into_inner();
+    match result {
+        Err(_) if !paths.is_empty() => Err(RepoError::CheckoutConflict {
+            message: format!(
+                "Local changes to {} file(s) would be overwritten by checkout.",
+                paths.len()
+            ),
+            paths,
+        }),
+        other => other,
+    }
+}
+
+fn checkout_local(
+    repo: &Repository,
+    name: &str,
+    builder: &mut CheckoutBuilder,
+) -> Result<(), RepoError> {
     let obj = repo
         .revparse_single(&format!("refs/heads/{name}"))
         .map_err(|e| RepoError::GitError {
             message: e.to_string(),
         })?;
-    let mut builder = CheckoutBuilder::new();
-    repo.checkout_tree(&obj, Some(&mut builder))
+    repo.checkout_tree(&obj, Some(builder))
         .map_err(|e| RepoError::GitError {
             message: e.to_string(),
         })?;
@@ -1257,7 +5030,33 @@ fn checkout_local(repo: &Repository, name: &str) -> Result<(), RepoError> {
     Ok(())
 }
 
-fn checkout_remote(repo: &Repository, name: &str) -> Result<(), RepoError> {
+fn checkout_detached(
+    repo: &Repository,
+    spec: &str,
+    builder: &mut CheckoutBuilder,
+) -> Result<(), RepoError> {
+    let commit = repo
+        .revparse_single(spec)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|e| RepoError::GitError {
+            message: e.to_string(),
+        })?;
+    repo.checkout_tree(commit.as_object(), Some(builder))
+        .map_err(|e| RepoError::GitError {
+            message: e.to_string(),
+        })?;
+    repo.set_head_detached(commit.id())
+        .map_err(|e| RepoError::GitError {
+            message: e.to_string(),
+        })?;
+    Ok(())
+}
+
+fn checkout_remote(
+    repo: &Repository,
+    name: &str,
+    builder: &mut CheckoutBuilder,
+) -> Result<(), RepoError> {
     let obj = repo.revparse_single(&format!("refs/remotes/{name}")).map_err(|e| {
         RepoError::GitError {
             message: e.to_string(),
@@ -1279,13 +5078,13 @@ fn checkout_remote(repo: &Repository, name: &str) -> Result<(), RepoError> {
             message: "not a commit".to_string(),
         })?;
 
-    repo.branch(&branch_name, &commit, false)
+    // Check out before creating the branch so a refused checkout leaves no
+    // stray local branch behind.
+    repo.checkout_tree(&obj, Some(builder))
         .map_err(|e| RepoError::GitError {
             message: e.to_string(),
         })?;
-
-    let mut builder = CheckoutBuilder::new();
-    repo.checkout_tree(&obj, Some(&mut builder))
+    repo.branch(&branch_name, &commit, false)
         .map_err(|e| RepoError::GitError {
             message: e.to_string(),
         })?;
@@ -0,0 +1,265 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::model::{CommitLintConfig, CommitWarning, ConventionalRule, RepoSummary, Severity};
+
+const CONVENTIONAL_SUMMARY: &str =
+    r"^(?P<type>[A-Za-z][A-Za-z0-9-]*)(?:\((?P<scope>[^()\s][^()]*)\))?!?: \S";
+
+/// `CONVENTIONAL_SUMMARY`, compiled on first use.
+fn conventional_grammar() -> &'static Regex {
+    static GRAMMAR: OnceLock<Regex> = OnceLock::new();
+    GRAMMAR.get_or_init(|| Regex::new(CONVENTIONAL_SUMMARY).expect("valid conventional regex"))
+}
+
+pub fn load_config(summary: &RepoSummary) -> Result<CommitLintConfig, String> {
+    let path = config_path(summary);
+    if !path.exists() {
+        return Ok(CommitLintConfig::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid {}: {e}", path.display()))
+}
+
+pub fn lint_message(config: &CommitLintConfig, message: &str) -> Vec<CommitWarning> {
+    let mut warnings = Vec::new();
+    let message = message.trim();
+    if message.is_empty() {
+        return warnings;
+    }
+    let lines: Vec<&str> = message.lines().map(str::trim_end).collect();
+    let subject = lines[0];
+
+    if let Some(rule) = &config.summary_length {
+        let length = subject.chars().count();
+        if length > rule.max {
+            warnings.push(warning(
+                "summary_length",
+                rule.severity,
+                format!("Summary is {length} characters; keep it to {}.", rule.max),
+            ));
+        }
+    }
+
+    if let Some(rule) = &config.blank_second_line {
+        if lines.get(1).is_some_and(|line| !line.is_empty()) {
+            warnings.push(warning(
+                "blank_second_line",
+                rule.severity,
+                "Separate the summary from the body with a blank line.".to_string(),
+            ));
+        }
+    }
+
+    if let Some(rule) = &config.body_wrap {
+        // Lines without whitespace, such as long URLs, cannot be wrapped.
+        for (index, line) in lines.iter().enumerate().skip(2) {
+            let length = line.chars().count();
+            if length > rule.width && line.contains(char::is_whitespace) {
+                warnings.push(warning(
+                    "body_wrap",
+                    rule.severity,
+                    format!(
+                        "Line {} is {length} characters; wrap the body at {}.",
+                        index + 1,
+                        rule.width
+                    ),
+                ));
+            }
+        }
+    }
+
+    if let Some(rule) = &config.conventional {
+        if let Some(problem) = conventional_problem(rule, subject) {
+            warnings.push(warning("conventional", rule.severity, problem));
+        }
+    }
+
+    if let Some(rule) = &config.ticket {
+        match Regex::new(&rule.pattern) {
+            Ok(pattern) if pattern.is_match(message) => {}
+            Ok(_) => warnings.push(warning(
+                "ticket",
+                rule.severity,
+                format!(
+                    "Message must reference a ticket matching `{}`.",
+                    rule.pattern
+                ),
+            )),
+            // A broken pattern is a config problem, so it never blocks.
+            Err(error) => warnings.push(warning(
+                "ticket",
+                Severity::Warning,
+                format!("Invalid ticket pattern `{}`: {error}", rule.pattern),
+            )),
+        }
+    }
+
+    warnings
+}
+
+fn conventional_problem(rule: &ConventionalRule, subject: &str) -> Option<String> {
+    let Some(captures) = conventional_grammar().captures(subject) else {
+        return Some(
+            "Summary must follow Conventional Commits: `type(scope): description`.".to_string(),
+        );
+    };
+    let kind = captures["type"].to_lowercase();
+    if !rule.types.is_empty() && !rule.types.iter().any(|known| known.to_lowercase() == kind) {
+        return Some(format!(
+            "Unknown commit type `{kind}`; use one of {}.",
+            rule.types.join(", ")
+        ));
+    }
+    match captures
+        .name("scope")
+        .map(|scope| scope.as_str().to_string())
+    {
+        None if rule.require_scope => {
+            Some("Summary needs a scope: `type(scope): description`.".to_string())
+        }
+        Some(scope) if !rule.scopes.is_empty() && !rule.scopes.contains(&scope) => Some(format!(
+            "Unknown scope `{scope}`; use one of {}.",
+            rule.scopes.join(", ")
+        )),
+        _ => None,
+    }
+}
+
+fn warning(rule: &str, severity: Severity, message: String) -> CommitWarning {
+    CommitWarning {
+        severity,
+        message,
+        rule: Some(rule.to_string()),
+    }
+}
+
+fn config_path(summary: &RepoSummary) -> PathBuf {
+    PathBuf::from(&summary.worktree_path)
+        .join(".gitpanel")
+        .join("commit-lint.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::lint_message;
+    use crate::model::{
+        BodyWrapRule, CommitLintConfig, ConventionalRule, LintRule, Severity, SummaryLengthRule,
+        TicketRule,
+    };
+
+    fn rules(config: &CommitLintConfig, message: &str) -> Vec<(String, Severity)> {
+        lint_message(config, message)
+            .into_iter()
+            .map(|warning| (warning.rule.unwrap_or_default(), warning.severity))
+            .collect()
+    }
+
+    #[test]
+    fn default_rules_check_summary_and_second_line() {
+        let config = CommitLintConfig::default();
+        assert!(rules(&config, "Short summary\n\nBody text.").is_empty());
+        let long = format!("{}\nbody right away", "x".repeat(73));
+        assert_eq!(
+            rules(&config, &long),
+            vec![
+                ("summary_length".to_string(), Severity::Warning),
+                ("blank_second_line".to_string(), Severity::Warning),
+            ]
+        );
+    }
+
+    #[test]
+    fn configured_rules_report_their_severity() {
+        let config: CommitLintConfig = serde_json::from_str(
+            r#"{
+                "body_wrap": { "width": 20 },
+                "conventional": {
+                    "types": ["feat", "fix"],
+                    "scopes": ["ui", "git"],
+                    "severity": "error"
+                },
+                "ticket": { "pattern": "GP-[0-9]+", "severity": "error" }
+            }"#,
+        )
+        .expect("parse config");
+        assert!(config.summary_length.is_none());
+
+        let valid = "feat(ui)!: add panel\n\n\
+                     Refs GP-12 and a very long line\n\
+                     https://example.com/a/very/long/url";
+        assert_eq!(
+            rules(&config, valid),
+            vec![("body_wrap".to_string(), Severity::Warning)]
+        );
+        assert_eq!(
+            rules(&config, "Add panel GP-1"),
+            vec![("conventional".to_string(), Severity::Error)]
+        );
+        assert_eq!(
+            rules(&config, "chore: tidy GP-1"),
+            vec![("conventional".to_string(), Severity::Error)]
+        );
+        assert_eq!(
+            rules(&config, "fix(api): tidy GP-1"),
+            vec![("conventional".to_string(), Severity::Error)]
+        );
+        assert_eq!(
+            rules(&config, "fix: tidy"),
+            vec![("ticket".to_string(), Severity::Error)]
+        );
+
+        let mixed_case = CommitLintConfig {
+            conventional: Some(ConventionalRule {
+                types: vec!["Feat".to_string()],
+                ..ConventionalRule::default()
+            }),
+            ..CommitLintConfig::default()
+        };
+        assert!(rules(&mixed_case, "feat: add panel").is_empty());
+        assert!(rules(&mixed_case, "FEAT: add panel").is_empty());
+    }
+
+    #[test]
+    fn scope_requirement_and_broken_patterns() {
+        let config = CommitLintConfig {
+            summary_length: Some(SummaryLengthRule {
+                max: 10,
+                severity: Severity::Error,
+            }),
+            blank_second_line: Some(LintRule {
+                severity: Severity::Error,
+            }),
+            body_wrap: Some(BodyWrapRule {
+                width: 72,
+                severity: Severity::Warning,
+            }),
+            conventional: Some(ConventionalRule {
+                require_scope: true,
+                ..ConventionalRule::default()
+            }),
+            ticket: Some(TicketRule {
+                pattern: "(".to_string(),
+                severity: Severity::Error,
+            }),
+        };
+        assert_eq!(
+            rules(&config, "fix: x"),
+            vec![
+                ("conventional".to_string(), Severity::Warning),
+                ("ticket".to_string(), Severity::Warning),
+            ]
+        );
+        assert_eq!(
+            rules(&config, "fix(core): long summary\nno blank"),
+            vec![
+                ("summary_length".to_string(), Severity::Error),
+                ("blank_second_line".to_string(), Severity::Error),
+                ("ticket".to_string(), Severity::Warning),
+            ]
+        );
+    }
+}
@@ -2,8 +2,10 @@
 
 mod api;
 mod changelist;
+mod credentials;
 mod git;
 mod jobs;
+mod lint;
 mod model;
 mod store;
 mod watch;
@@ -31,19 +33,53 @@ fn main() {
             api::repo_stage,
             api::repo_track,
             api::repo_unstage,
+            api::repo_conflict_versions,
+            api::repo_conflict_resolve,
+            api::repo_conflict_mark_resolved,
             api::repo_branches,
             api::repo_checkout,
             api::repo_create_branch,
+            api::repo_branch_delete,
+            api::repo_branch_rename,
+            api::repo_branch_set_upstream,
+            api::repo_remote_branch_delete,
+            api::repo_tags,
+            api::repo_tag_create,
+            api::repo_tag_delete,
+            api::repo_tag_push,
             api::repo_fetch,
+            api::repo_pull,
+            api::repo_push,
+            api::repo_log,
+            api::repo_commit_detail,
+            api::repo_diff_range,
+            api::repo_blame,
+            api::repo_file_history,
+            api::repo_operation_continue,
+            api::repo_operation_abort,
+            api::repo_operation_skip,
+            api::repo_revert,
+            api::repo_cherry_pick,
+            api::repo_reset,
+            api::stash_list,
+            api::stash_save,
+            api::stash_apply,
+            api::stash_pop,
+            api::stash_drop,
+            api::stash_show,
             api::cl_list,
             api::cl_create,
             api::cl_rename,
+            api::cl_set_draft,
             api::cl_delete,
             api::cl_set_active,
             api::cl_assign_files,
             api::cl_unassign_files,
             api::cl_assign_hunks,
             api::cl_unassign_hunks,
+            api::cl_shelve,
+            api::cl_unshelve,
+            api::cl_shelf_list,
             api::commit_prepare,
             api::commit_execute,
             api::repo_list_recent,
@@ -24,6 +24,8 @@ pub struct RepoDiffRequest {
 pub enum RepoDiffKind {
     Unstaged,
     Staged,
+    Commit { oid: String },
+    Range { from: String, to: String },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,14 +43,159 @@ pub struct RepoBranchListRequest {
 pub struct RepoCheckoutRequest {
     pub repo_id: RepoId,
     pub target: CheckoutTarget,
+    #[serde(default)]
+    pub mode: CheckoutMode,
+}
+
+/// How local changes are handled when switching branches.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum CheckoutMode {
+    /// Refuse to switch while the working tree is dirty.
+    #[default]
+    Clean,
+    /// Stash local changes, switch, then re-apply the stash.
+    Stash,
+    /// Keep local changes when they do not touch files that differ between
+    /// the two branches.
+    Carry,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepoCreateBranchRequest {
     pub repo_id: RepoId,
     pub name: String,
+    /// Defaults to HEAD, which may be detached.
     #[serde(default)]
     pub from: Option<String>,
+    /// Switch to the new branch, keeping local changes.
+    #[serde(default)]
+    pub checkout: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoBranchDeleteRequest {
+    pub repo_id: RepoId,
+    pub name: String,
+    /// Delete even when the branch is not merged into its upstream or HEAD.
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoBranchRenameRequest {
+    pub repo_id: RepoId,
+    pub name: String,
+    pub new_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoBranchUpstreamRequest {
+    pub repo_id: RepoId,
+    pub name: String,
+    /// Remote-tracking branch such as `origin/main`; `None` unsets it.
+    #[serde(default)]
+    pub upstream: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoRemoteBranchDeleteRequest {
+    pub repo_id: RepoId,
+    #[serde(default)]
+    pub remote: Option<String>,
+    pub branch: String,
+    #[serde(default)]
+    pub credentials: Option<CredentialInput>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoTagCreateRequest {
+    pub repo_id: RepoId,
+    pub name: String,
+    /// Defaults to HEAD.
+    #[serde(default)]
+    pub revision: Option<String>,
+    /// Creates an annotated tag when set, a lightweight tag otherwise.
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoTagDeleteRequest {
+    pub repo_id: RepoId,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoTagPushRequest {
+    pub repo_id: RepoId,
+    #[serde(default)]
+    pub remote: Option<String>,
+    /// Pushes every tag when omitted.
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub credentials: Option<CredentialInput>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoRevertRequest {
+    pub repo_id: RepoId,
+    pub revision: String,
+    /// Leave the changes in the working tree instead of committing them.
+    #[serde(default)]
+    pub no_commit: bool,
+    /// With `no_commit`, the changelist the touched files are assigned to.
+    #[serde(default)]
+    pub changelist_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoCherryPickRequest {
+    pub repo_id: RepoId,
+    pub revision: String,
+    /// Leave the changes in the working tree instead of committing them.
+    #[serde(default)]
+    pub no_commit: bool,
+    /// With `no_commit`, the changelist the touched files are assigned to.
+    #[serde(default)]
+    pub changelist_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitApplyResult {
+    pub head: RepoHead,
+    /// The new commit; omitted with `no_commit`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_id: Option<String>,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ResetMode {
+    Soft,
+    Mixed,
+    Hard,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoResetRequest {
+    pub repo_id: RepoId,
+    pub revision: String,
+    pub mode: ResetMode,
+    /// Required for hard resets: the one-time token from the last
+    /// `ConfirmationRequired` for this repo and revision.
+    #[serde(default)]
+    pub confirm: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResetResult {
+    pub head: RepoHead,
+    /// Ref holding the pre-reset HEAD and uncommitted changes (hard only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_ref: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +203,307 @@ pub struct RepoFetchRequest {
     pub repo_id: RepoId,
     #[serde(default)]
     pub remote: Option<String>,
+    #[serde(default)]
+    pub credentials: Option<CredentialInput>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoPushRequest {
+    pub repo_id: RepoId,
+    #[serde(default)]
+    pub remote: Option<String>,
+    #[serde(default)]
+    pub branch: Option<String>,
+    #[serde(default)]
+    pub set_upstream: bool,
+    #[serde(default)]
+    pub credentials: Option<CredentialInput>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoPullRequest {
+    pub repo_id: RepoId,
+    #[serde(default)]
+    pub strategy: PullStrategy,
+    #[serde(default)]
+    pub credentials: Option<CredentialInput>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CredentialInput {
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub ssh_key_path: Option<String>,
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CredentialKind {
+    SshKey,
+    UserPassword,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PullStrategy {
+    #[default]
+    FastForward,
+    Merge,
+    Rebase,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoLogRequest {
+    pub repo_id: RepoId,
+    /// A ref, oid or range (`a..b`, `a...b`); defaults to HEAD.
+    #[serde(default)]
+    pub revision: Option<String>,
+    /// `next_cursor` from the previous page.
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitSignature {
+    pub name: String,
+    pub email: String,
+    /// Seconds since the Unix epoch.
+    pub time: i64,
+    pub offset_minutes: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub oid: String,
+    pub parents: Vec<String>,
+    pub author: CommitSignature,
+    pub committer: CommitSignature,
+    pub summary: String,
+    pub refs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogPage {
+    pub entries: Vec<LogEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoCommitDetailRequest {
+    pub repo_id: RepoId,
+    pub oid: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+    Copied,
+    TypeChange,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitFileChange {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    pub change: FileChangeKind,
+    pub binary: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitDetail {
+    pub commit: LogEntry,
+    pub message: String,
+    pub files: Vec<CommitFileChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoRangeDiffRequest {
+    pub repo_id: RepoId,
+    /// A revision, or an `a..b` / `a...b` range when `to` is omitted.
+    pub from: String,
+    #[serde(default)]
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffFileSummary {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    pub change: FileChangeKind,
+    pub binary: bool,
+    pub additions: u32,
+    pub deletions: u32,
+}
+
+/// Hunks for a file are loaded on demand with `RepoDiffKind::Range` using
+/// the resolved `from`/`to` oids.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangeDiff {
+    pub from: String,
+    pub to: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merge_base: Option<String>,
+    pub files: Vec<DiffFileSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoBlameRequest {
+    pub repo_id: RepoId,
+    pub path: String,
+    /// Defaults to HEAD.
+    #[serde(default)]
+    pub revision: Option<String>,
+    /// Blame the working-tree file on top of `revision`.
+    #[serde(default)]
+    pub working_tree: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlameRange {
+    /// One-based line number in the blamed file.
+    pub start_line: u32,
+    pub line_count: u32,
+    pub oid: String,
+    pub committed: bool,
+    pub author: CommitSignature,
+    pub summary: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orig_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlameResult {
+    pub path: String,
+    pub ranges: Vec<BlameRange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoFileHistoryRequest {
+    pub repo_id: RepoId,
+    pub path: String,
+    /// Defaults to HEAD.
+    #[serde(default)]
+    pub revision: Option<String>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileHistoryEntry {
+    pub commit: LogEntry,
+    /// The file's path in this commit.
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    pub change: FileChangeKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileHistoryPage {
+    pub entries: Vec<FileHistoryEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictBlob {
+    pub oid: String,
+    pub path: String,
+    pub binary: bool,
+    /// UTF-8 text of the blob; omitted for binary content.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+/// The index stages of a conflicted path; a missing side was deleted there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictVersions {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<ConflictBlob>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ours: Option<ConflictBlob>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theirs: Option<ConflictBlob>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ConflictResolution {
+    Ours,
+    Theirs,
+    Merged { content: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictResolveRequest {
+    pub repo_id: RepoId,
+    pub path: String,
+    pub resolution: ConflictResolution,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashEntry {
+    /// Position in the stash list (`stash@{index}`).
+    pub index: u32,
+    pub oid: String,
+    pub message: String,
+    pub time: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashSaveRequest {
+    pub repo_id: RepoId,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub include_untracked: bool,
+    #[serde(default)]
+    pub keep_index: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashIndexRequest {
+    pub repo_id: RepoId,
+    pub index: u32,
+}
+
+/// Addresses a stash by index together with the oid the UI listed, so a
+/// stash list that changed underneath cannot act on the wrong entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashEntryRequest {
+    pub repo_id: RepoId,
+    pub index: u32,
+    pub oid: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashDetail {
+    pub entry: StashEntry,
+    pub files: Vec<CommitFileChange>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,15 +522,62 @@ pub struct RepoStatus {
     pub head: RepoHead,
     pub counts: RepoCounts,
     pub files: Vec<StatusFile>,
+    #[serde(default)]
+    pub operation: RepoOperation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rebase_progress: Option<OperationProgress>,
+}
+
+/// Mirrors `git2::RepositoryState`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum RepoOperation {
+    #[default]
+    None,
+    Merge,
+    Revert,
+    RevertSequence,
+    CherryPick,
+    CherryPickSequence,
+    Bisect,
+    Rebase,
+    RebaseInteractive,
+    RebaseMerge,
+    ApplyMailbox,
+    ApplyMailboxOrRebase,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct OperationProgress {
+    pub current: u32,
+    pub total: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BranchList {
+    /// Empty when HEAD is detached.
     pub current: String,
     pub locals: Vec<String>,
     pub remotes: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ahead_behind: Option<std::collections::HashMap<String, AheadBehind>>,
+    /// Upstream branch (e.g. `origin/main`) keyed by local branch name.
+    #[serde(default)]
+    pub upstreams: std::collections::HashMap<String, String>,
+    #[serde(default)]
+    pub tags: Vec<TagInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagInfo {
+    pub name: String,
+    /// The commit the tag points at, peeled through annotated tags.
+    pub target: String,
+    pub annotated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tagger: Option<CommitSignature>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -103,16 +598,30 @@ pub struct CheckoutTarget {
 pub enum CheckoutTargetKind {
     Local,
     Remote,
+    /// Detaches HEAD at the tagged commit.
+    Tag,
+    /// Detaches HEAD at any revision `git rev-parse` understands.
+    Commit,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CheckoutResult {
     pub head: RepoHead,
+    /// Paths left conflicted by re-applying stashed changes.
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+    /// The stash that still holds the local changes when re-applying them
+    /// conflicted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stash: Option<StashEntry>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BranchCreateResult {
     pub name: String,
+    /// Set when the new branch was checked out.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<RepoHead>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,17 +630,130 @@ pub struct FetchResult {
     pub updated: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushResult {
+    pub remote: String,
+    pub branch: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullResult {
+    pub head: RepoHead,
+    pub upstream: String,
+    pub updated_files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushRejection {
+    pub reference: String,
+    pub reason: PushRejectReason,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PushRejectReason {
+    NonFastForward,
+    HookDeclined,
+    Other,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum RepoError {
     DirtyWorkingTree { message: String },
     GitError { message: String },
+    PushRejected {
+        message: String,
+        rejections: Vec<PushRejection>,
+    },
+    MergeConflict {
+        message: String,
+        paths: Vec<String>,
+    },
+    CheckoutConflict {
+        message: String,
+        paths: Vec<String>,
+    },
+    BranchNotMerged {
+        message: String,
+        branch: String,
+    },
+    BranchExists {
+        message: String,
+        branch: String,
+    },
+    BranchCheckedOut {
+        message: String,
+        branch: String,
+    },
+    /// Error-severity commit-lint rules failed for the message.
+    CommitLintFailed {
+        message: String,
+        violations: Vec<CommitWarning>,
+    },
+    /// A commit hook exited non-zero; `output` is its stdout and stderr.
+    HookFailed {
+        message: String,
+        hook: String,
+        exit_code: Option<i32>,
+        output: String,
+    },
+    /// Shelving was refused: these changelist files have both staged and
+    /// unstaged changes.
+    PartiallyStaged {
+        message: String,
+        paths: Vec<String>,
+    },
+    /// The checkout failed and its auto-stash could not be restored; the
+    /// local changes are kept in `stash`.
+    StashRestoreFailed {
+        message: String,
+        stash: StashEntry,
+    },
+    /// Resend the request with `token` to confirm a destructive action.
+    ConfirmationRequired {
+        message: String,
+        token: String,
+    },
+    AuthRequired {
+        message: String,
+        url: String,
+        username: Option<String>,
+        allowed: Vec<CredentialKind>,
+    },
+}
+
+impl RepoError {
+    pub fn message(&self) -> &str {
+        match self {
+            RepoError::DirtyWorkingTree { message }
+            | RepoError::GitError { message }
+            | RepoError::PushRejected { message, .. }
+            | RepoError::MergeConflict { message, .. }
+            | RepoError::CheckoutConflict { message, .. }
+            | RepoError::BranchNotMerged { message, .. }
+            | RepoError::BranchExists { message, .. }
+            | RepoError::BranchCheckedOut { message, .. }
+            | RepoError::CommitLintFailed { message, .. }
+            | RepoError::HookFailed { message, .. }
+            | RepoError::PartiallyStaged { message, .. }
+            | RepoError::StashRestoreFailed { message, .. }
+            | RepoError::ConfirmationRequired { message, .. }
+            | RepoError::AuthRequired { message, .. } => message,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepoHead {
-    pub branch_name: String,
+    /// `None` when HEAD is detached.
+    pub branch_name: Option<String>,
     pub oid_short: String,
+    #[serde(default)]
+    pub detached: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -211,6 +833,9 @@ pub struct Changelist {
     pub id: String,
     pub name: String,
     pub created_at: u64,
+    /// Unsent commit message typed for this changelist.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub draft: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -220,6 +845,9 @@ pub struct ChangelistState {
     pub assignments: std::collections::HashMap<String, String>,
     #[serde(default)]
     pub hunk_assignments: std::collections::HashMap<String, HunkAssignmentSet>,
+    /// Recently committed messages, newest first.
+    #[serde(default)]
+    pub message_history: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -241,6 +869,13 @@ pub struct ChangelistRenameRequest {
     pub name: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangelistDraftRequest {
+    pub repo_id: RepoId,
+    pub id: String,
+    pub message: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChangelistAssignRequest {
     pub repo_id: RepoId,
@@ -264,6 +899,19 @@ pub struct HunkAssignment {
     pub new_lines: u32,
     pub content_hash: String,
     pub kind: RepoDiffKind,
+    /// Selected lines within the hunk; `None` selects the whole hunk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_ranges: Option<Vec<HunkLineRange>>,
+    /// Hunk body at assignment time, used to re-anchor after nearby edits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+/// Inclusive, zero-based range of lines in `DiffHunk::content`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HunkLineRange {
+    pub start: u32,
+    pub end: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -287,16 +935,80 @@ pub struct ChangelistUnassignHunksRequest {
     pub hunk_ids: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShelfEntry {
+    pub id: String,
+    pub changelist_id: String,
+    pub changelist_name: String,
+    pub created_at: u64,
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub hunk_assignments: std::collections::HashMap<String, Vec<HunkAssignment>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShelfIdRequest {
+    pub repo_id: RepoId,
+    pub shelf_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnshelveResult {
+    pub shelf_id: String,
+    pub changelist_id: String,
+    pub restored_paths: Vec<String>,
+    pub conflicts: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitPrepareRequest {
     pub repo_id: RepoId,
     pub changelist_id: String,
+    /// Message to lint against the repo's commit rules; skipped when absent.
+    #[serde(default)]
+    pub message: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CommitOptions {
     #[serde(default)]
     pub amend: bool,
+    /// Overrides `commit.gpgsign` for this commit.
+    #[serde(default)]
+    pub sign: Option<bool>,
+    /// Skip the `pre-commit` and `commit-msg` hooks.
+    #[serde(default)]
+    pub no_verify: bool,
+    #[serde(default)]
+    pub author: Option<CommitAuthor>,
+    /// With `amend`, take the author from the current identity instead of
+    /// keeping the amended commit's author.
+    #[serde(default)]
+    pub reset_author: bool,
+    /// Appends a `Signed-off-by` trailer for the committer.
+    #[serde(default)]
+    pub signoff: bool,
+    #[serde(default)]
+    pub trailers: Vec<CommitTrailer>,
+}
+
+/// Unset fields fall back to the configured identity and the current time.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CommitAuthor {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Any date format git accepts, e.g. RFC 2822 or ISO 8601.
+    #[serde(default)]
+    pub date: Option<String>,
+}
+
+/// A `key: value` trailer such as `Co-authored-by: Name <email>`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitTrailer {
+    pub key: String,
+    pub value: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -313,7 +1025,7 @@ pub struct CommitPreview {
     pub changelist_id: String,
     pub files: Vec<StatusFile>,
     pub stats: RepoCounts,
-    pub warnings: Vec<String>,
+    pub warnings: Vec<CommitWarning>,
     pub hunk_files: Vec<String>,
     pub invalid_hunks: Vec<HunkAssignment>,
 }
@@ -323,6 +1035,113 @@ pub struct CommitResult {
     pub head: RepoHead,
     pub commit_id: String,
     pub committed_paths: Vec<String>,
+    #[serde(default)]
+    pub signature: SignatureStatus,
+    pub author: CommitSignature,
+    pub committer: CommitSignature,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitWarning {
+    pub severity: Severity,
+    pub message: String,
+    /// The commit-lint rule that produced the warning, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    #[default]
+    Warning,
+    /// Blocks the commit.
+    Error,
+}
+
+/// Commit message rules read from `.gitpanel/commit-lint.json` in the
+/// worktree. Rules missing from the file are off; without a file the
+/// summary length and blank second line checks warn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitLintConfig {
+    #[serde(default)]
+    pub summary_length: Option<SummaryLengthRule>,
+    #[serde(default)]
+    pub blank_second_line: Option<LintRule>,
+    #[serde(default)]
+    pub body_wrap: Option<BodyWrapRule>,
+    #[serde(default)]
+    pub conventional: Option<ConventionalRule>,
+    #[serde(default)]
+    pub ticket: Option<TicketRule>,
+}
+
+impl Default for CommitLintConfig {
+    fn default() -> Self {
+        Self {
+            summary_length: Some(SummaryLengthRule {
+                max: 72,
+                severity: Severity::Warning,
+            }),
+            blank_second_line: Some(LintRule::default()),
+            body_wrap: None,
+            conventional: None,
+            ticket: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LintRule {
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryLengthRule {
+    pub max: usize,
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BodyWrapRule {
+    pub width: usize,
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+/// `type(scope)!: description` summaries. Empty `types` or `scopes` allow
+/// any value.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ConventionalRule {
+    #[serde(default)]
+    pub types: Vec<String>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub require_scope: bool,
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+/// The message must contain a match for `pattern`, e.g. `[A-Z]+-[0-9]+`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketRule {
+    pub pattern: String,
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SignatureStatus {
+    #[default]
+    Unsigned,
+    /// Signed with a good signature from a trusted key.
+    Verified,
+    /// Signed, but the signature could not be checked or did not match.
+    Unverified,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,4 +1,7 @@
+use std::collections::hash_map::RandomState;
 use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::jobs::JobQueue;
@@ -11,11 +14,22 @@ pub struct CachedStatus {
     pub updated_at_ms: u64,
 }
 
+/// A hard reset awaiting confirmation: the nonce handed to the client and
+/// the HEAD it was issued against.
+struct PendingConfirmation {
+    nonce: String,
+    head: String,
+}
+
 pub struct AppState {
     recent: Vec<RepoListItem>,
     repos: HashMap<RepoId, RepoSummary>,
     status_cache: HashMap<RepoId, CachedStatus>,
     diff_cache: HashMap<String, UnifiedDiffText>,
+    /// Per-file patches of whole-tree diffs, keyed by repo and oid pair.
+    tree_diff_cache: HashMap<String, Arc<HashMap<String, String>>>,
+    /// Pending hard-reset confirmations, keyed by repo and target revision.
+    reset_confirmations: HashMap<String, PendingConfirmation>,
     watchers: HashMap<RepoId, RepoWatcher>,
     pub job_queue: JobQueue,
 }
@@ -27,6 +41,8 @@ impl Default for AppState {
             repos: HashMap::new(),
             status_cache: HashMap::new(),
             diff_cache: HashMap::new(),
+            tree_diff_cache: HashMap::new(),
+            reset_confirmations: HashMap::new(),
             watchers: HashMap::new(),
             job_queue: JobQueue::default(),
         }
@@ -75,6 +91,52 @@ impl AppState {
         self.diff_cache.insert(key, value);
     }
 
+    pub fn get_tree_diff_cache(&self, key: &str) -> Option<Arc<HashMap<String, String>>> {
+        self.tree_diff_cache.get(key).cloned()
+    }
+
+    pub fn set_tree_diff_cache(&mut self, key: String, value: Arc<HashMap<String, String>>) {
+        if self.tree_diff_cache.len() > 16 {
+            if let Some(first_key) = self.tree_diff_cache.keys().next().cloned() {
+                self.tree_diff_cache.remove(&first_key);
+            }
+        }
+        self.tree_diff_cache.insert(key, value);
+    }
+
+    /// Issues a one-time nonce confirming a hard reset of `repo_id` to
+    /// `target` while HEAD is `head`, replacing any earlier one.
+    pub fn issue_reset_confirmation(
+        &mut self,
+        repo_id: &RepoId,
+        target: &str,
+        head: String,
+    ) -> String {
+        let nonce = confirmation_nonce();
+        self.reset_confirmations.insert(
+            confirmation_key(repo_id, target),
+            PendingConfirmation {
+                nonce: nonce.clone(),
+                head,
+            },
+        );
+        nonce
+    }
+
+    /// Consumes the pending confirmation for `repo_id` and `target`,
+    /// returning the HEAD it was issued against when `nonce` matches.
+    pub fn take_reset_confirmation(
+        &mut self,
+        repo_id: &RepoId,
+        target: &str,
+        nonce: &str,
+    ) -> Option<String> {
+        let pending = self
+            .reset_confirmations
+            .remove(&confirmation_key(repo_id, target))?;
+        (pending.nonce == nonce).then_some(pending.head)
+    }
+
     pub fn upsert_watcher(&mut self, repo_id: &RepoId, watcher: RepoWatcher) {
         self.watchers.insert(repo_id.clone(), watcher);
     }
@@ -98,6 +160,25 @@ impl AppState {
     }
 }
 
+fn confirmation_key(repo_id: &RepoId, target: &str) -> String {
+    format!("{repo_id}\0{target}")
+}
+
+/// 128 bits from two randomly keyed hashers; std has no RNG of its own.
+fn confirmation_nonce() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let mut nonce = String::new();
+    for _ in 0..2 {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(nanos);
+        nonce.push_str(&format!("{:016x}", hasher.finish()));
+    }
+    nonce
+}
+
 pub fn now_ts() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -1,25 +1,48 @@
 import { invoke } from "@tauri-apps/api/core";
 import type {
   AppVersion,
+  BlameResult,
   BranchCreateResult,
   BranchList,
   Changelist,
   ChangelistState,
+  CommitApplyResult,
+  CommitDetail,
   CommitOptions,
   CommitPreview,
   CommitResult,
+  ConflictResolution,
+  ConflictVersions,
+  CredentialInput,
   DiffHunk,
   HunkAssignment,
+  LogPage,
+  CheckoutMode,
   CheckoutResult,
   CheckoutTarget,
   FetchResult,
+  FileHistoryPage,
+  PullResult,
+  PullStrategy,
+  PushResult,
+  RangeDiff,
   RepoListItem,
   RepoDiffPayload,
   RepoDiffKind,
+  RepoFileHistoryRequest,
+  RepoLogRequest,
   RepoStatus,
   RepoStatusRequest,
   RepoSummary,
+  ResetMode,
+  ResetResult,
+  ShelfEntry,
+  StashDetail,
+  StashEntry,
+  StashSaveRequest,
+  TagInfo,
   UnifiedDiffText,
+  UnshelveResult,
   WorktreeList,
   WorktreeResult
 } from "../types/ipc";
@@ -77,6 +100,25 @@ export async function repoUnstage(repo_id: string, path: string): Promise<void>
   return invoke("repo_unstage", { req: { repo_id, path } });
 }
 
+export async function repoConflictVersions(
+  repo_id: string,
+  path: string
+): Promise<ConflictVersions> {
+  return invoke("repo_conflict_versions", { req: { repo_id, path } });
+}
+
+export async function repoConflictResolve(
+  repo_id: string,
+  path: string,
+  resolution: ConflictResolution
+): Promise<void> {
+  return invoke("repo_conflict_resolve", { req: { repo_id, path, resolution } });
+}
+
+export async function repoConflictMarkResolved(repo_id: string, path: string): Promise<void> {
+  return invoke("repo_conflict_mark_resolved", { req: { repo_id, path } });
+}
+
 export async function repoListRecent(): Promise<RepoListItem[]> {
   return invoke("repo_list_recent");
 }
@@ -91,24 +133,214 @@ export async function repoBranches(repo_id: string): Promise<BranchList> {
 
 export async function repoCheckout(
   repo_id: string,
-  target: CheckoutTarget
+  target: CheckoutTarget,
+  mode?: CheckoutMode
 ): Promise<CheckoutResult> {
-  return invoke("repo_checkout", { req: { repo_id, target } });
+  return invoke("repo_checkout", { req: { repo_id, target, mode } });
 }
 
 export async function repoCreateBranch(
   repo_id: string,
   name: string,
-  from?: string
+  from?: string,
+  checkout?: boolean
 ): Promise<BranchCreateResult> {
-  return invoke("repo_create_branch", { req: { repo_id, name, from } });
+  return invoke("repo_create_branch", { req: { repo_id, name, from, checkout } });
+}
+
+export async function repoBranchDelete(
+  repo_id: string,
+  name: string,
+  force?: boolean
+): Promise<void> {
+  return invoke("repo_branch_delete", { req: { repo_id, name, force } });
+}
+
+export async function repoBranchRename(
+  repo_id: string,
+  name: string,
+  new_name: string
+): Promise<void> {
+  return invoke("repo_branch_rename", { req: { repo_id, name, new_name } });
+}
+
+export async function repoBranchSetUpstream(
+  repo_id: string,
+  name: string,
+  upstream: string | null
+): Promise<void> {
+  return invoke("repo_branch_set_upstream", { req: { repo_id, name, upstream } });
+}
+
+export async function repoRemoteBranchDelete(
+  repo_id: string,
+  branch: string,
+  remote?: string,
+  credentials?: CredentialInput
+): Promise<void> {
+  return invoke("repo_remote_branch_delete", { req: { repo_id, remote, branch, credentials } });
+}
+
+export async function repoTags(repo_id: string): Promise<TagInfo[]> {
+  return invoke("repo_tags", { req: { repo_id } });
+}
+
+export async function repoTagCreate(
+  repo_id: string,
+  name: string,
+  options: { revision?: string; message?: string } = {}
+): Promise<TagInfo> {
+  return invoke("repo_tag_create", { req: { repo_id, name, ...options } });
+}
+
+export async function repoTagDelete(repo_id: string, name: string): Promise<void> {
+  return invoke("repo_tag_delete", { req: { repo_id, name } });
+}
+
+export async function repoTagPush(
+  repo_id: string,
+  options: { remote?: string; name?: string; credentials?: CredentialInput } = {}
+): Promise<void> {
+  return invoke("repo_tag_push", { req: { repo_id, ...options } });
 }
 
 export async function repoFetch(
   repo_id: string,
-  remote?: string
+  remote?: string,
+  credentials?: CredentialInput
 ): Promise<FetchResult> {
-  return invoke("repo_fetch", { req: { repo_id, remote } });
+  return invoke("repo_fetch", { req: { repo_id, remote, credentials } });
+}
+
+export async function repoPull(
+  repo_id: string,
+  strategy?: PullStrategy,
+  credentials?: CredentialInput
+): Promise<PullResult> {
+  return invoke("repo_pull", { req: { repo_id, strategy, credentials } });
+}
+
+export async function repoPush(
+  repo_id: string,
+  options: {
+    remote?: string;
+    branch?: string;
+    set_upstream?: boolean;
+    credentials?: CredentialInput;
+  } = {}
+): Promise<PushResult> {
+  return invoke("repo_push", { req: { repo_id, ...options } });
+}
+
+export async function repoLog(
+  repo_id: string,
+  options: Omit<RepoLogRequest, "repo_id"> = {}
+): Promise<LogPage> {
+  return invoke("repo_log", { req: { repo_id, ...options } });
+}
+
+export async function repoCommitDetail(
+  repo_id: string,
+  oid: string
+): Promise<CommitDetail> {
+  return invoke("repo_commit_detail", { req: { repo_id, oid } });
+}
+
+export async function repoDiffRange(
+  repo_id: string,
+  from: string,
+  to?: string
+): Promise<RangeDiff> {
+  return invoke("repo_diff_range", { req: { repo_id, from, to } });
+}
+
+export async function repoBlame(
+  repo_id: string,
+  path: string,
+  options: { revision?: string; working_tree?: boolean } = {}
+): Promise<BlameResult> {
+  return invoke("repo_blame", { req: { repo_id, path, ...options } });
+}
+
+export async function repoFileHistory(
+  repo_id: string,
+  path: string,
+  options: Omit<RepoFileHistoryRequest, "repo_id" | "path"> = {}
+): Promise<FileHistoryPage> {
+  return invoke("repo_file_history", { req: { repo_id, path, ...options } });
+}
+
+export async function repoOperationContinue(repo_id: string): Promise<void> {
+  return invoke("repo_operation_continue", { req: { repo_id } });
+}
+
+export async function repoOperationAbort(repo_id: string): Promise<void> {
+  return invoke("repo_operation_abort", { req: { repo_id } });
+}
+
+export async function repoOperationSkip(repo_id: string): Promise<void> {
+  return invoke("repo_operation_skip", { req: { repo_id } });
+}
+
+export async function repoRevert(
+  repo_id: string,
+  revision: string,
+  options: { no_commit?: boolean; changelist_id?: string } = {}
+): Promise<CommitApplyResult> {
+  return invoke("repo_revert", { req: { repo_id, revision, ...options } });
+}
+
+export async function repoCherryPick(
+  repo_id: string,
+  revision: string,
+  options: { no_commit?: boolean; changelist_id?: string } = {}
+): Promise<CommitApplyResult> {
+  return invoke("repo_cherry_pick", { req: { repo_id, revision, ...options } });
+}
+
+export async function repoReset(
+  repo_id: string,
+  revision: string,
+  mode: ResetMode,
+  confirm?: string
+): Promise<ResetResult> {
+  return invoke("repo_reset", { req: { repo_id, revision, mode, confirm } });
+}
+
+export async function stashList(repo_id: string): Promise<StashEntry[]> {
+  return invoke("stash_list", { req: { repo_id } });
+}
+
+export async function stashSave(
+  repo_id: string,
+  options: Omit<StashSaveRequest, "repo_id"> = {}
+): Promise<StashEntry> {
+  return invoke("stash_save", { req: { repo_id, ...options } });
+}
+
+export async function stashApply(
+  repo_id: string,
+  stash: Pick<StashEntry, "index" | "oid">
+): Promise<void> {
+  return invoke("stash_apply", { req: { repo_id, index: stash.index, oid: stash.oid } });
+}
+
+export async function stashPop(
+  repo_id: string,
+  stash: Pick<StashEntry, "index" | "oid">
+): Promise<void> {
+  return invoke("stash_pop", { req: { repo_id, index: stash.index, oid: stash.oid } });
+}
+
+export async function stashDrop(
+  repo_id: string,
+  stash: Pick<StashEntry, "index" | "oid">
+): Promise<void> {
+  return invoke("stash_drop", { req: { repo_id, index: stash.index, oid: stash.oid } });
+}
+
+export async function stashShow(repo_id: string, index: number): Promise<StashDetail> {
+  return invoke("stash_show", { req: { repo_id, index } });
 }
 
 export async function clList(repo_id: string): Promise<ChangelistState> {
@@ -123,6 +355,10 @@ export async function clRename(repo_id: string, id: string, name: string): Promi
   return invoke("cl_rename", { req: { repo_id, id, name } });
 }
 
+export async function clSetDraft(repo_id: string, id: string, message: string): Promise<void> {
+  return invoke("cl_set_draft", { req: { repo_id, id, message } });
+}
+
 export async function clDelete(repo_id: string, id: string): Promise<void> {
   return invoke("cl_delete", { req: { repo_id, id } });
 }
@@ -160,11 +396,24 @@ export async function clUnassignHunks(
   return invoke("cl_unassign_hunks", { req: { repo_id, path, hunk_ids } });
 }
 
+export async function clShelve(repo_id: string, id: string): Promise<ShelfEntry> {
+  return invoke("cl_shelve", { req: { repo_id, id } });
+}
+
+export async function clUnshelve(repo_id: string, shelf_id: string): Promise<UnshelveResult> {
+  return invoke("cl_unshelve", { req: { repo_id, shelf_id } });
+}
+
+export async function clShelfList(repo_id: string): Promise<ShelfEntry[]> {
+  return invoke("cl_shelf_list", { req: { repo_id } });
+}
+
 export async function commitPrepare(
   repo_id: string,
-  changelist_id: string
+  changelist_id: string,
+  message?: string
 ): Promise<CommitPreview> {
-  return invoke("commit_prepare", { req: { repo_id, changelist_id } });
+  return invoke("commit_prepare", { req: { repo_id, changelist_id, message } });
 }
 
 export async function commitExecute(
@@ -96,7 +96,11 @@ const MOCK_BRANCHES: BranchList = {
   remotes: ["origin/main"],
   ahead_behind: {
     main: { ahead: 1, behind: 0 }
-  }
+  },
+  upstreams: {
+    main: "origin/main"
+  },
+  tags: []
 };
 
 const MOCK_WORKTREES: WorktreeList = {
@@ -208,7 +212,8 @@ const createMockState = (): MockState => {
       repo_id: REPO_ID,
       head: {
         branch_name: "main",
-        oid_short: "a1b2c3d"
+        oid_short: "a1b2c3d",
+        detached: false
       },
       counts: {
         staged: 0,
@@ -216,7 +221,8 @@ const createMockState = (): MockState => {
         untracked: 0,
         conflicted: 0
       },
-      files
+      files,
+      operation: "none"
     },
     changelists: {
       lists: [DEFAULT_CHANGE_LIST, FEATURE_CHANGE_LIST],
@@ -226,7 +232,8 @@ const createMockState = (): MockState => {
         [MIXED_FILE_PATH]: DEFAULT_CHANGE_LIST_ID,
         [UNVERSIONED_FILE_PATH]: DEFAULT_CHANGE_LIST_ID
       },
-      hunk_assignments: {}
+      hunk_assignments: {},
+      message_history: []
     }
   };
   syncStatusFromChangelists(state);
@@ -306,7 +313,7 @@ export function installE2ETauriMocks() {
         case "repo_fetch":
           return { remote: "origin", updated: false };
         case "repo_checkout":
-          return { head: runtime.status.head };
+          return { head: runtime.status.head, conflicts: [] };
         case "repo_stage": {
           const path = typeof req.path === "string" ? req.path : "";
           if (path) {
@@ -14,7 +14,11 @@ export interface RepoDiffRequest {
   kind: RepoDiffKind;
 }
 
-export type RepoDiffKind = "unstaged" | "staged";
+export type RepoDiffKind =
+  | "unstaged"
+  | "staged"
+  | { commit: { oid: string } }
+  | { range: { from: string; to: string } };
 
 export interface RepoPathRequest {
   repo_id: RepoId;
@@ -28,17 +32,302 @@ export interface RepoBranchListRequest {
 export interface RepoCheckoutRequest {
   repo_id: RepoId;
   target: CheckoutTarget;
+  mode?: CheckoutMode;
 }
 
+export type CheckoutMode = "clean" | "stash" | "carry";
+
 export interface RepoCreateBranchRequest {
   repo_id: RepoId;
   name: string;
   from?: string;
+  checkout?: boolean;
+}
+
+export interface RepoBranchDeleteRequest {
+  repo_id: RepoId;
+  name: string;
+  force?: boolean;
+}
+
+export interface RepoBranchRenameRequest {
+  repo_id: RepoId;
+  name: string;
+  new_name: string;
+}
+
+export interface RepoBranchUpstreamRequest {
+  repo_id: RepoId;
+  name: string;
+  upstream?: string | null;
+}
+
+export interface RepoRemoteBranchDeleteRequest {
+  repo_id: RepoId;
+  remote?: string;
+  branch: string;
+  credentials?: CredentialInput;
+}
+
+export interface RepoTagCreateRequest {
+  repo_id: RepoId;
+  name: string;
+  revision?: string;
+  message?: string;
+}
+
+export interface RepoTagDeleteRequest {
+  repo_id: RepoId;
+  name: string;
+}
+
+export interface RepoTagPushRequest {
+  repo_id: RepoId;
+  remote?: string;
+  name?: string;
+  credentials?: CredentialInput;
 }
 
+export interface RepoRevertRequest {
+  repo_id: RepoId;
+  revision: string;
+  no_commit?: boolean;
+  changelist_id?: string;
+}
+
+export interface RepoCherryPickRequest {
+  repo_id: RepoId;
+  revision: string;
+  no_commit?: boolean;
+  changelist_id?: string;
+}
+
+export interface CommitApplyResult {
+  head: RepoHead;
+  commit_id?: string;
+  paths: string[];
+}
+
+export type ResetMode = "soft" | "mixed" | "hard";
+
+export interface RepoResetRequest {
+  repo_id: RepoId;
+  revision: string;
+  mode: ResetMode;
+  confirm?: string;
+}
+
+export interface ResetResult {
+  head: RepoHead;
+  backup_ref?: string;
+}
+
+export interface CredentialInput {
+  username?: string;
+  password?: string;
+  ssh_key_path?: string;
+  passphrase?: string;
+}
+
+export type CredentialKind = "sshKey" | "userPassword";
+
 export interface RepoFetchRequest {
   repo_id: RepoId;
   remote?: string;
+  credentials?: CredentialInput;
+}
+
+export interface RepoPushRequest {
+  repo_id: RepoId;
+  remote?: string;
+  branch?: string;
+  set_upstream?: boolean;
+  credentials?: CredentialInput;
+}
+
+export type PullStrategy = "fastForward" | "merge" | "rebase";
+
+export interface RepoPullRequest {
+  repo_id: RepoId;
+  strategy?: PullStrategy;
+  credentials?: CredentialInput;
+}
+
+export interface RepoLogRequest {
+  repo_id: RepoId;
+  revision?: string;
+  cursor?: string;
+  limit?: number;
+  path?: string;
+  author?: string;
+  message?: string;
+}
+
+export interface CommitSignature {
+  name: string;
+  email: string;
+  time: number;
+  offset_minutes: number;
+}
+
+export interface LogEntry {
+  oid: string;
+  parents: string[];
+  author: CommitSignature;
+  committer: CommitSignature;
+  summary: string;
+  refs: string[];
+}
+
+export interface LogPage {
+  entries: LogEntry[];
+  next_cursor?: string;
+}
+
+export interface RepoCommitDetailRequest {
+  repo_id: RepoId;
+  oid: string;
+}
+
+export type FileChangeKind =
+  | "added"
+  | "modified"
+  | "deleted"
+  | "renamed"
+  | "copied"
+  | "typechange";
+
+export interface CommitFileChange {
+  path: string;
+  old_path?: string;
+  change: FileChangeKind;
+  binary: boolean;
+  hunks: DiffHunk[];
+}
+
+export interface CommitDetail {
+  commit: LogEntry;
+  message: string;
+  files: CommitFileChange[];
+}
+
+export interface RepoRangeDiffRequest {
+  repo_id: RepoId;
+  from: string;
+  to?: string;
+}
+
+export interface DiffFileSummary {
+  path: string;
+  old_path?: string;
+  change: FileChangeKind;
+  binary: boolean;
+  additions: number;
+  deletions: number;
+}
+
+export interface RangeDiff {
+  from: string;
+  to: string;
+  merge_base?: string;
+  files: DiffFileSummary[];
+}
+
+export interface RepoBlameRequest {
+  repo_id: RepoId;
+  path: string;
+  revision?: string;
+  working_tree?: boolean;
+}
+
+export interface BlameRange {
+  start_line: number;
+  line_count: number;
+  oid: string;
+  committed: boolean;
+  author: CommitSignature;
+  summary: string;
+  orig_path?: string;
+}
+
+export interface BlameResult {
+  path: string;
+  ranges: BlameRange[];
+}
+
+export interface RepoFileHistoryRequest {
+  repo_id: RepoId;
+  path: string;
+  revision?: string;
+  cursor?: string;
+  limit?: number;
+}
+
+export interface FileHistoryEntry {
+  commit: LogEntry;
+  path: string;
+  old_path?: string;
+  change: FileChangeKind;
+}
+
+export interface FileHistoryPage {
+  entries: FileHistoryEntry[];
+  next_cursor?: string;
+}
+
+export interface ConflictBlob {
+  oid: string;
+  path: string;
+  binary: boolean;
+  content?: string;
+}
+
+export interface ConflictVersions {
+  path: string;
+  base?: ConflictBlob;
+  ours?: ConflictBlob;
+  theirs?: ConflictBlob;
+}
+
+export type ConflictResolution =
+  | { type: "ours" }
+  | { type: "theirs" }
+  | { type: "merged"; content: string };
+
+export interface ConflictResolveRequest {
+  repo_id: RepoId;
+  path: string;
+  resolution: ConflictResolution;
+}
+
+export interface StashEntry {
+  index: number;
+  oid: string;
+  message: string;
+  time: number;
+}
+
+export interface StashSaveRequest {
+  repo_id: RepoId;
+  message?: string;
+  include_untracked?: boolean;
+  keep_index?: boolean;
+}
+
+export interface StashIndexRequest {
+  repo_id: RepoId;
+  index: number;
+}
+
+export interface StashEntryRequest {
+  repo_id: RepoId;
+  index: number;
+  oid: string;
+}
+
+export interface StashDetail {
+  entry: StashEntry;
+  files: CommitFileChange[];
 }
 
 export interface RepoSummary {
@@ -55,11 +344,33 @@ export interface RepoStatus {
   head: RepoHead;
   counts: RepoCounts;
   files: StatusFile[];
+  operation: RepoOperation;
+  rebase_progress?: OperationProgress;
+}
+
+export type RepoOperation =
+  | "none"
+  | "merge"
+  | "revert"
+  | "revertSequence"
+  | "cherryPick"
+  | "cherryPickSequence"
+  | "bisect"
+  | "rebase"
+  | "rebaseInteractive"
+  | "rebaseMerge"
+  | "applyMailbox"
+  | "applyMailboxOrRebase";
+
+export interface OperationProgress {
+  current: number;
+  total: number;
 }
 
 export interface RepoHead {
-  branch_name: string;
+  branch_name: string | null;
   oid_short: string;
+  detached: boolean;
 }
 
 export interface RepoCounts {
@@ -124,6 +435,7 @@ export interface Changelist {
   id: string;
   name: string;
   created_at: number;
+  draft?: string;
 }
 
 export interface ChangelistState {
@@ -131,6 +443,7 @@ export interface ChangelistState {
   active_id: string;
   assignments: Record<string, string>;
   hunk_assignments: Record<string, HunkAssignmentSet>;
+  message_history: string[];
 }
 
 export interface ChangelistCreateRequest {
@@ -144,6 +457,12 @@ export interface ChangelistRenameRequest {
   name: string;
 }
 
+export interface ChangelistDraftRequest {
+  repo_id: RepoId;
+  id: string;
+  message: string;
+}
+
 export interface ChangelistIdRequest {
   repo_id: RepoId;
   id: string;
@@ -169,6 +488,13 @@ export interface HunkAssignment {
   new_lines: number;
   content_hash: string;
   kind: RepoDiffKind;
+  line_ranges?: HunkLineRange[];
+  content?: string;
+}
+
+export interface HunkLineRange {
+  start: number;
+  end: number;
 }
 
 export interface HunkAssignmentSet {
@@ -189,13 +515,52 @@ export interface ChangelistUnassignHunksRequest {
   hunk_ids: string[];
 }
 
+export interface ShelfEntry {
+  id: string;
+  changelist_id: string;
+  changelist_name: string;
+  created_at: number;
+  files: string[];
+  hunk_assignments: Record<string, HunkAssignment[]>;
+}
+
+export interface ShelfIdRequest {
+  repo_id: RepoId;
+  shelf_id: string;
+}
+
+export interface UnshelveResult {
+  shelf_id: string;
+  changelist_id: string;
+  restored_paths: string[];
+  conflicts: string[];
+}
+
 export interface CommitPrepareRequest {
   repo_id: RepoId;
   changelist_id: string;
+  message?: string | null;
 }
 
 export interface CommitOptions {
   amend?: boolean;
+  sign?: boolean;
+  no_verify?: boolean;
+  author?: CommitAuthor | null;
+  reset_author?: boolean;
+  signoff?: boolean;
+  trailers?: CommitTrailer[];
+}
+
+export interface CommitAuthor {
+  name?: string | null;
+  email?: string | null;
+  date?: string | null;
+}
+
+export interface CommitTrailer {
+  key: string;
+  value: string;
 }
 
 export interface CommitExecuteRequest {
@@ -209,17 +574,30 @@ export interface CommitPreview {
   changelist_id: string;
   files: StatusFile[];
   stats: RepoCounts;
-  warnings: string[];
+  warnings: CommitWarning[];
   hunk_files: string[];
   invalid_hunks: HunkAssignment[];
 }
 
+export type Severity = "warning" | "error";
+
+export interface CommitWarning {
+  severity: Severity;
+  message: string;
+  rule?: string;
+}
+
 export interface CommitResult {
   head: RepoHead;
   commit_id: string;
   committed_paths: string[];
+  signature: SignatureStatus;
+  author: CommitSignature;
+  committer: CommitSignature;
 }
 
+export type SignatureStatus = "unsigned" | "verified" | "unverified";
+
 export interface RepoContext {
   repo_root: string;
   worktree_path: string;
@@ -263,19 +641,32 @@ export interface BranchList {
   locals: string[];
   remotes: string[];
   ahead_behind?: Record<string, { ahead: number; behind: number }>;
+  upstreams: Record<string, string>;
+  tags: TagInfo[];
+}
+
+export interface TagInfo {
+  name: string;
+  target: string;
+  annotated: boolean;
+  message?: string;
+  tagger?: CommitSignature;
 }
 
 export interface CheckoutTarget {
-  type: "local" | "remote";
+  type: "local" | "remote" | "tag" | "commit";
   name: string;
 }
 
 export interface CheckoutResult {
   head: RepoHead;
+  conflicts: string[];
+  stash?: StashEntry;
 }
 
 export interface BranchCreateResult {
   name: string;
+  head?: RepoHead;
 }
 
 export interface FetchResult {
@@ -283,6 +674,50 @@ export interface FetchResult {
   updated: boolean;
 }
 
+export interface PushResult {
+  remote: string;
+  branch: string;
+  upstream?: string;
+}
+
+export interface PullResult {
+  head: RepoHead;
+  upstream: string;
+  updated_files: string[];
+}
+
+export type PushRejectReason = "nonFastForward" | "hookDeclined" | "other";
+
+export interface PushRejection {
+  reference: string;
+  reason: PushRejectReason;
+  message: string;
+}
+
 export type RepoError =
-  | { type: "DirtyWorkingTree"; message: string }
-  | { type: "GitError"; message: string };
+  | { type: "dirtyWorkingTree"; message: string }
+  | { type: "gitError"; message: string }
+  | { type: "pushRejected"; message: string; rejections: PushRejection[] }
+  | { type: "mergeConflict"; message: string; paths: string[] }
+  | { type: "checkoutConflict"; message: string; paths: string[] }
+  | { type: "branchNotMerged"; message: string; branch: string }
+  | { type: "branchExists"; message: string; branch: string }
+  | { type: "branchCheckedOut"; message: string; branch: string }
+  | { type: "commitLintFailed"; message: string; violations: CommitWarning[] }
+  | {
+      type: "hookFailed";
+      message: string;
+      hook: string;
+      exit_code: number | null;
+      output: string;
+    }
+  | { type: "partiallyStaged"; message: string; paths: string[] }
+  | { type: "stashRestoreFailed"; message: string; stash: StashEntry }
+  | { type: "confirmationRequired"; message: string; token: string }
+  | {
+      type: "authRequired";
+      message: string;
+      url: string;
+      username?: string | null;
+      allowed: CredentialKind[];
+    };
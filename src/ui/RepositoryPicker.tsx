@@ -856,7 +856,7 @@ export default function RepositoryPicker() {
       setBranches(branchList);
     } catch (error) {
       const typed = error as RepoError;
-      if (typed?.type === "DirtyWorkingTree") {
+      if (typed?.type === "dirtyWorkingTree") {
         setToast("Checkout blocked: working tree has uncommitted changes.");
       } else {
         setToast("Checkout failed. See console for details.");
@@ -1180,7 +1180,8 @@ export default function RepositoryPicker() {
           new_start: hunk.new_start,
           new_lines: hunk.new_lines,
           content_hash: hunk.content_hash,
-          kind: hunk.kind
+          kind: hunk.kind,
+          content: hunk.content
         }))
       );
       await handleRefresh();
@@ -1646,7 +1647,7 @@ export default function RepositoryPicker() {
             {commitPreview && commitPreview.warnings.length > 0 && (
               <ul className="commit-warnings">
                 {commitPreview.warnings.map((warning) => (
-                  <li key={warning}>{warning}</li>
+                  <li key={`${warning.rule ?? ""}:${warning.message}`}>{warning.message}</li>
                 ))}
               </ul>
             )}